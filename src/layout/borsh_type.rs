@@ -1,14 +1,26 @@
+use super::{Definitions, PubkeyStrategy, TypeResolver};
+
+use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
 
 /// Types that can be represented in a TypsScript borsh schema
 /// for (de)serialization.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BorshType {
     U8,
     U16,
     U32,
     U64,
     U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    F32,
+    F64,
     Bool,
     String,
     Pubkey,
@@ -17,125 +29,331 @@ pub enum BorshType {
     FixedBytes(usize),
     Option(Box<BorshType>),
     Map(Box<BorshType>, Box<BorshType>),
+    /// A `HashSet` or `BTreeSet`, which is serialized the same way as a
+    /// `Vec` of its elements.
+    Set(Box<BorshType>),
+    /// A tuple of positional types, the unit type `()` is an empty tuple.
+    ///
+    /// `borsh-js` has no tuple schema, so every tuple type is emitted as a
+    /// `struct` layout named after the types of its elements, e.g.
+    /// `TupleU8_Pubkey`, with the fields `unnamed_0`, `unnamed_1` and so on.
+    Tuple(Vec<BorshType>),
     Custom(String),
+    /// An instantiation of a generic type, e.g. `Wrapper<u64>`.
+    Generic(String, Vec<BorshType>),
+    /// A generic type parameter of the layout, e.g. `T` in `Wrapper<T>`.
+    TypeParam(String),
     Skip,
 }
 
+/// An error of the TypeScript class and borsh schema emitters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EmitError {
+    /// A skipped type, e.g. `PhantomData`, is nested in a type that is
+    /// serialized, so it has no TypeScript or schema representation. Skipped
+    /// types are only allowed as the type of a field.
+    NestedSkip,
+}
+
+impl fmt::Display for EmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NestedSkip => write!(
+                f,
+                "skipped types (e.g. `PhantomData`) can only be used as the type of a field"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EmitError {}
+
 impl FromStr for BorshType {
     type Err = anyhow::Error;
-    fn from_str(input_str: &str) -> Result<Self, Self::Err> {
-        let mut input = input_str.to_owned();
-        input.retain(|c| !c.is_whitespace());
-        match input.as_ref() {
-            "u8" | "i8" => Ok(BorshType::U8),
-            "u16" | "i16" => Ok(BorshType::U16),
-            "u32" | "i32" => Ok(BorshType::U32),
-            "u64" | "i64" | "UnixTimestamp" => Ok(BorshType::U64),
-            "u128" | "i128" => Ok(BorshType::U128),
-            "bool" => Ok(BorshType::Bool),
-            "String" => Ok(BorshType::String),
-            "Pubkey" => Ok(BorshType::Pubkey),
-            _ => {
-                if let Some(inner) = input.strip_prefix("Option<") {
-                    let inner = inner
-                        .strip_suffix('>')
-                        .ok_or_else(|| anyhow::anyhow!("invalid Option"))?;
-                    let inner_type = BorshType::from_str(inner)?;
-                    Ok(BorshType::Option(Box::new(inner_type)))
-                } else if let Some(inner) = input.strip_prefix("Vec<") {
-                    let inner = inner
-                        .strip_suffix('>')
-                        .ok_or_else(|| anyhow::anyhow!("invalid Vec"))?;
-                    let inner_type = BorshType::from_str(inner)?;
-                    Ok(BorshType::Vec(Box::new(inner_type)))
-                } else if let Some(inner) = input.strip_prefix("VecDeque<") {
-                    let inner = inner
-                        .strip_suffix('>')
-                        .ok_or_else(|| anyhow::anyhow!("invalid VecDeque"))?;
-                    let inner_type = BorshType::from_str(inner)?;
-                    Ok(BorshType::Vec(Box::new(inner_type)))
-                } else if let Some(inner) = input.strip_prefix('[') {
-                    let inner = inner
-                        .strip_suffix(']')
-                        .ok_or_else(|| anyhow::anyhow!("invalid array, missing ']'"))?;
-                    let (array_type_str, array_len_str) = inner
-                        .rsplit_once(';')
-                        .ok_or_else(|| anyhow::anyhow!("invalid array, missing ';'"))?;
-                    let array_type = BorshType::from_str(array_type_str)?;
-                    let array_len = array_len_str.parse::<usize>()?;
-                    if let BorshType::U8 = array_type {
-                        Ok(BorshType::FixedBytes(array_len))
-                    } else {
-                        Ok(BorshType::FixedArray(Box::new(array_type), array_len))
-                    }
-                } else if let Some(inner) = input.strip_prefix("BTreeMap<") {
-                    let inner = inner
-                        .strip_suffix('>')
-                        .ok_or_else(|| anyhow::anyhow!("invalid BTreeMap"))?;
-                    let (key_str, value_str) = inner
-                        .split_once(',')
-                        .ok_or_else(|| anyhow::anyhow!("invalid BTreeMap, missing ','"))?;
-                    let key = BorshType::from_str(key_str)?;
-                    let value = BorshType::from_str(value_str)?;
-                    Ok(BorshType::Map(Box::new(key), Box::new(value)))
-                } else {
-                    Ok(BorshType::Custom(input.to_owned()))
-                }
-            }
-        }
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        BorshType::try_from(&syn::parse_str::<syn::Type>(input)?)
+    }
+}
+
+impl TryFrom<&syn::Type> for BorshType {
+    type Error = anyhow::Error;
+    fn try_from(ty: &syn::Type) -> Result<Self, Self::Error> {
+        TypeResolver::new(&Definitions::default()).resolve(ty)
     }
 }
 
 impl BorshType {
     /// Converts the type to a schema representation used by
     /// [`borsh-js`](https://github.com/near/borsh-js).
-    pub fn to_borsh_schema(&self) -> String {
-        match self {
+    pub fn to_borsh_schema(&self) -> Result<String, EmitError> {
+        self.to_borsh_schema_with_pubkey(PubkeyStrategy::default())
+    }
+
+    /// Converts the type to a schema representation using a custom
+    /// representation of public keys.
+    pub fn to_borsh_schema_with_pubkey(&self, pubkey: PubkeyStrategy) -> Result<String, EmitError> {
+        let schema = match self {
             Self::U8 | Self::Bool => "'u8'".to_owned(),
             Self::U16 => "'u16'".to_owned(),
             Self::U32 => "'u32'".to_owned(),
             Self::U64 => "'u64'".to_owned(),
             Self::U128 => "'u128'".to_owned(),
+            Self::I8 => "'i8'".to_owned(),
+            Self::I16 => "'i16'".to_owned(),
+            Self::I32 => "'i32'".to_owned(),
+            Self::I64 => "'i64'".to_owned(),
+            Self::I128 => "'i128'".to_owned(),
+            Self::F32 => "'f32'".to_owned(),
+            Self::F64 => "'f64'".to_owned(),
             Self::String => "'string'".to_owned(),
-            Self::Pubkey => "PublicKeyBE".to_owned(),
-            Self::Vec(inner) => format!("[{}]", inner.to_borsh_schema()),
-            Self::FixedArray(inner, len) => format!("[{}, {}]", inner.to_borsh_schema(), len),
+            Self::Pubkey => pubkey.schema_type().to_owned(),
+            Self::Vec(inner) | Self::Set(inner) => {
+                format!("[{}]", inner.to_borsh_schema_with_pubkey(pubkey)?)
+            }
+            Self::FixedArray(inner, len) => {
+                format!("[{}, {}]", inner.to_borsh_schema_with_pubkey(pubkey)?, len)
+            }
             Self::FixedBytes(len) => format!("[{}]", len),
             Self::Option(inner) => {
-                format!("{{ kind: 'option', type: {} }}", inner.to_borsh_schema())
+                format!(
+                    "{{ kind: 'option', type: {} }}",
+                    inner.to_borsh_schema_with_pubkey(pubkey)?
+                )
             }
             Self::Map(key, value) => format!(
                 "{{ kind: 'map', key: {}, value: {} }}",
-                key.to_borsh_schema(),
-                value.to_borsh_schema()
+                key.to_borsh_schema_with_pubkey(pubkey)?,
+                value.to_borsh_schema_with_pubkey(pubkey)?
             ),
+            Self::Tuple(elems) => {
+                if elems.iter().any(|elem| elem.contains_skip()) {
+                    return Err(EmitError::NestedSkip);
+                }
+                self.mangled_name()
+            }
             Self::Custom(inner) => inner.to_owned(),
-            _ => unimplemented!(),
-        }
+            Self::Generic(name, _) => name.to_owned(),
+            Self::TypeParam(name) => name.to_owned(),
+            Self::Skip => return Err(EmitError::NestedSkip),
+        };
+        Ok(schema)
     }
 
     /// Converts the type to a TypeScript class type.
-    pub fn to_class_type(&self) -> String {
-        match self {
+    pub fn to_class_type(&self) -> Result<String, EmitError> {
+        self.to_class_type_with_pubkey(PubkeyStrategy::default())
+    }
+
+    /// Converts the type to a TypeScript class type using a custom
+    /// representation of public keys.
+    pub fn to_class_type_with_pubkey(&self, pubkey: PubkeyStrategy) -> Result<String, EmitError> {
+        let class_type = match self {
             Self::U8 => "number".to_owned(),
             Self::U16 => "number".to_owned(),
             Self::U32 => "number".to_owned(),
             Self::U64 => "BN".to_owned(),
             Self::U128 => "BN".to_owned(),
+            Self::I8 => "number".to_owned(),
+            Self::I16 => "number".to_owned(),
+            Self::I32 => "number".to_owned(),
+            Self::I64 => "BN".to_owned(),
+            Self::I128 => "BN".to_owned(),
+            Self::F32 => "number".to_owned(),
+            Self::F64 => "number".to_owned(),
             Self::Bool => "boolean".to_owned(),
             Self::String => "string".to_owned(),
-            Self::Pubkey => "PublicKeyBE".to_owned(),
-            Self::Vec(inner) => format!("{}[]", inner.to_class_type()),
-            Self::FixedArray(inner, _len) => format!("{}[]", inner.to_class_type()),
+            Self::Pubkey => pubkey.class_type().to_owned(),
+            Self::Vec(inner) | Self::Set(inner) => {
+                format!("{}[]", inner.to_class_type_with_pubkey(pubkey)?)
+            }
+            Self::FixedArray(inner, _len) => {
+                format!("{}[]", inner.to_class_type_with_pubkey(pubkey)?)
+            }
             Self::FixedBytes(len) => format!("[{}]", len),
             Self::Option(inner) => {
-                format!("{} | null", inner.to_class_type())
+                format!("{} | null", inner.to_class_type_with_pubkey(pubkey)?)
             }
             Self::Map(key, value) => {
-                format!("Map<{}, {}>", key.to_class_type(), value.to_class_type())
+                format!(
+                    "Map<{}, {}>",
+                    key.to_class_type_with_pubkey(pubkey)?,
+                    value.to_class_type_with_pubkey(pubkey)?
+                )
+            }
+            Self::Tuple(elems) => {
+                if elems.iter().any(|elem| elem.contains_skip()) {
+                    return Err(EmitError::NestedSkip);
+                }
+                self.mangled_name()
             }
             Self::Custom(inner) => inner.to_owned(),
-            _ => unimplemented!(),
+            Self::Generic(name, args) => format!(
+                "{}<{}>",
+                name,
+                args.iter()
+                    .map(|arg| arg.to_class_type_with_pubkey(pubkey))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join(", ")
+            ),
+            Self::TypeParam(name) => name.to_owned(),
+            Self::Skip => return Err(EmitError::NestedSkip),
+        };
+        Ok(class_type)
+    }
+
+    /// Returns `true` if the type contains a floating point number that
+    /// needs to be checked for `NaN` values.
+    pub fn contains_float(&self) -> bool {
+        match self {
+            Self::F32 | Self::F64 => true,
+            Self::Vec(inner)
+            | Self::FixedArray(inner, _)
+            | Self::Option(inner)
+            | Self::Set(inner) => inner.contains_float(),
+            Self::Map(key, value) => key.contains_float() || value.contains_float(),
+            Self::Tuple(elems) => elems.iter().any(|elem| elem.contains_float()),
+            Self::Generic(_, args) => args.iter().any(|arg| arg.contains_float()),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the type contains a skipped type, e.g. `PhantomData`.
+    pub fn contains_skip(&self) -> bool {
+        let mut found = false;
+        self.map(&mut |ty| {
+            found |= *ty == Self::Skip;
+            None
+        });
+        found
+    }
+
+    /// Returns `true` if the type contains a generic type parameter.
+    pub fn contains_type_params(&self) -> bool {
+        let mut found = false;
+        self.map(&mut |ty| {
+            found |= matches!(ty, Self::TypeParam(_));
+            None
+        });
+        found
+    }
+
+    /// Replaces every tuple with a reference to the `struct` layout it is
+    /// emitted as, see [`BorshType::Tuple`].
+    pub fn lift_tuples(&self) -> BorshType {
+        self.map(&mut |ty| match ty {
+            Self::Tuple(_) => Some(Self::Custom(ty.mangled_name())),
+            _ => None,
+        })
+    }
+
+    /// Collects the tuples found in the type, including nested ones.
+    pub fn tuples(&self, tuples: &mut Vec<BorshType>) {
+        self.map(&mut |ty| {
+            if let Self::Tuple(_) = ty {
+                tuples.push(ty.clone());
+            }
+            None
+        });
+    }
+
+    /// Replaces generic type parameters with the types they are bound to.
+    pub fn substitute(&self, bindings: &HashMap<String, BorshType>) -> BorshType {
+        self.map(&mut |ty| match ty {
+            Self::TypeParam(name) => bindings.get(name).cloned(),
+            _ => None,
+        })
+    }
+
+    /// Replaces every generic type instantiation with a reference to its
+    /// monomorphized layout.
+    pub fn monomorphize(&self) -> BorshType {
+        self.map(&mut |ty| match ty {
+            Self::Generic(..) => Some(Self::Custom(ty.mangled_name())),
+            _ => None,
+        })
+    }
+
+    /// Collects the generic type instantiations found in the type.
+    pub fn generic_instances(&self, instances: &mut Vec<(String, Vec<BorshType>)>) {
+        self.map(&mut |ty| {
+            if let Self::Generic(name, args) = ty {
+                instances.push((name.clone(), args.clone()));
+            }
+            None
+        });
+    }
+
+    /// Collects the names of the layouts the type refers to.
+    pub fn referenced_layouts(&self, names: &mut Vec<String>) {
+        self.map(&mut |ty| {
+            if let Self::Custom(name) | Self::Generic(name, _) = ty {
+                names.push(name.clone());
+            }
+            None
+        });
+    }
+
+    /// Renames the referenced layouts found in `names`.
+    pub fn rename_layouts(&self, names: &HashMap<String, String>) -> BorshType {
+        self.map(&mut |ty| match ty {
+            Self::Custom(name) => names.get(name).cloned().map(Self::Custom),
+            Self::Generic(name, args) => names.get(name).map(|renamed| {
+                Self::Generic(
+                    renamed.clone(),
+                    args.iter().map(|arg| arg.rename_layouts(names)).collect(),
+                )
+            }),
+            _ => None,
+        })
+    }
+
+    /// Returns a name that is unique for every type and can be used as a
+    /// TypeScript identifier, e.g. `WrapperVecU64_OptionPubkey` for
+    /// `Wrapper<Vec<u64>, Option<Pubkey>>`. Type arguments and tuple elements
+    /// are separated by `_` and array lengths precede the type of the
+    /// elements, so `Pair<FooBar, Baz>` and `Pair<Foo, BarBaz>` get different
+    /// names.
+    pub fn mangled_name(&self) -> String {
+        let join = |types: &[BorshType]| {
+            types
+                .iter()
+                .map(|ty| ty.mangled_name())
+                .collect::<Vec<_>>()
+                .join("_")
+        };
+        match self {
+            Self::Vec(inner) => format!("Vec{}", inner.mangled_name()),
+            Self::FixedArray(inner, len) => format!("Array{}{}", len, inner.mangled_name()),
+            Self::FixedBytes(len) => format!("Bytes{}", len),
+            Self::Option(inner) => format!("Option{}", inner.mangled_name()),
+            Self::Map(key, value) => {
+                format!("Map{}_{}", key.mangled_name(), value.mangled_name())
+            }
+            Self::Set(inner) => format!("Set{}", inner.mangled_name()),
+            Self::Tuple(elems) => format!("Tuple{}", join(elems)),
+            Self::Custom(name) | Self::TypeParam(name) => name.to_owned(),
+            Self::Generic(name, args) => format!("{}{}", name, join(args)),
+            _ => format!("{:?}", self),
+        }
+    }
+
+    /// Rebuilds the type bottom-up, replacing every (sub)type for which `f`
+    /// returns `Some`.
+    fn map(&self, f: &mut dyn FnMut(&BorshType) -> Option<BorshType>) -> BorshType {
+        if let Some(replaced) = f(self) {
+            return replaced;
+        }
+        match self {
+            Self::Vec(inner) => Self::Vec(Box::new(inner.map(f))),
+            Self::FixedArray(inner, len) => Self::FixedArray(Box::new(inner.map(f)), *len),
+            Self::Option(inner) => Self::Option(Box::new(inner.map(f))),
+            Self::Map(key, value) => Self::Map(Box::new(key.map(f)), Box::new(value.map(f))),
+            Self::Set(inner) => Self::Set(Box::new(inner.map(f))),
+            Self::Tuple(elems) => Self::Tuple(elems.iter().map(|elem| elem.map(f)).collect()),
+            Self::Generic(name, args) => {
+                Self::Generic(name.clone(), args.iter().map(|arg| arg.map(f)).collect())
+            }
+            _ => self.clone(),
         }
     }
 }
@@ -148,15 +366,21 @@ mod test {
     fn simple_type_from_str() {
         assert_eq!(BorshType::from_str("bool").unwrap(), BorshType::Bool);
         assert_eq!(BorshType::from_str("u8").unwrap(), BorshType::U8);
-        assert_eq!(BorshType::from_str("i8").unwrap(), BorshType::U8);
+        assert_eq!(BorshType::from_str("i8").unwrap(), BorshType::I8);
         assert_eq!(BorshType::from_str("u16").unwrap(), BorshType::U16);
-        assert_eq!(BorshType::from_str("i16").unwrap(), BorshType::U16);
+        assert_eq!(BorshType::from_str("i16").unwrap(), BorshType::I16);
         assert_eq!(BorshType::from_str("u32").unwrap(), BorshType::U32);
-        assert_eq!(BorshType::from_str("i32").unwrap(), BorshType::U32);
+        assert_eq!(BorshType::from_str("i32").unwrap(), BorshType::I32);
         assert_eq!(BorshType::from_str("u64").unwrap(), BorshType::U64);
-        assert_eq!(BorshType::from_str("i64").unwrap(), BorshType::U64);
+        assert_eq!(BorshType::from_str("i64").unwrap(), BorshType::I64);
         assert_eq!(BorshType::from_str("u128").unwrap(), BorshType::U128);
-        assert_eq!(BorshType::from_str("i128").unwrap(), BorshType::U128);
+        assert_eq!(BorshType::from_str("i128").unwrap(), BorshType::I128);
+        assert_eq!(
+            BorshType::from_str("UnixTimestamp").unwrap(),
+            BorshType::I64
+        );
+        assert_eq!(BorshType::from_str("f32").unwrap(), BorshType::F32);
+        assert_eq!(BorshType::from_str("f64").unwrap(), BorshType::F64);
         assert_eq!(BorshType::from_str("String").unwrap(), BorshType::String);
         assert_eq!(BorshType::from_str("Pubkey").unwrap(), BorshType::Pubkey);
         assert_eq!(
@@ -189,7 +413,7 @@ mod test {
             BorshType::from_str("[[Option<i32>; 2]; 4]").unwrap(),
             BorshType::FixedArray(
                 Box::new(BorshType::FixedArray(
-                    Box::new(BorshType::Option(Box::new(BorshType::U32))),
+                    Box::new(BorshType::Option(Box::new(BorshType::I32))),
                     2
                 )),
                 4
@@ -209,56 +433,294 @@ mod test {
         );
     }
 
+    #[test]
+    fn path_type_from_str() {
+        assert_eq!(
+            BorshType::from_str("std::vec::Vec<u8>").unwrap(),
+            BorshType::Vec(Box::new(BorshType::U8))
+        );
+        assert_eq!(
+            BorshType::from_str("alloc::string::String").unwrap(),
+            BorshType::String
+        );
+        assert_eq!(
+            BorshType::from_str("Option < Vec < Foo > >").unwrap(),
+            BorshType::Option(Box::new(BorshType::Vec(Box::new(BorshType::Custom(
+                "Foo".to_owned()
+            )))))
+        );
+        assert_eq!(
+            BorshType::from_str("crate::state::Config").unwrap(),
+            BorshType::Custom("Config".to_owned())
+        );
+        assert_eq!(
+            BorshType::from_str("BTreeMap<Vec<(u8,u8)>, u8>").unwrap(),
+            BorshType::Map(
+                Box::new(BorshType::Vec(Box::new(BorshType::Tuple(vec![
+                    BorshType::U8,
+                    BorshType::U8
+                ])))),
+                Box::new(BorshType::U8)
+            )
+        );
+        assert_eq!(
+            BorshType::from_str("HashMap<String, u64, RandomState>").unwrap(),
+            BorshType::Map(Box::new(BorshType::String), Box::new(BorshType::U64))
+        );
+        assert_eq!(
+            BorshType::from_str("&'a [u16]").unwrap(),
+            BorshType::Vec(Box::new(BorshType::U16))
+        );
+        assert_eq!(BorshType::from_str("&str").unwrap(), BorshType::String);
+    }
+
+    #[test]
+    fn wrapper_type_from_str() {
+        assert_eq!(
+            BorshType::from_str("Box<Node>").unwrap(),
+            BorshType::Custom("Node".to_owned())
+        );
+        assert_eq!(
+            BorshType::from_str("Option< Box<Node> >").unwrap(),
+            BorshType::Option(Box::new(BorshType::Custom("Node".to_owned())))
+        );
+        assert_eq!(
+            BorshType::from_str("std::rc::Rc<Config>").unwrap(),
+            BorshType::Custom("Config".to_owned())
+        );
+        assert_eq!(
+            BorshType::from_str("Arc<Vec<u64>>").unwrap(),
+            BorshType::Vec(Box::new(BorshType::U64))
+        );
+        assert_eq!(
+            BorshType::from_str("Cow<'a, str>").unwrap(),
+            BorshType::String
+        );
+        assert_eq!(
+            BorshType::from_str("Cow<'static, [Pubkey]>").unwrap(),
+            BorshType::Vec(Box::new(BorshType::Pubkey))
+        );
+        assert_eq!(
+            BorshType::from_str("std::marker::PhantomData<T>").unwrap(),
+            BorshType::Skip
+        );
+    }
+
+    #[test]
+    fn invalid_type_from_str() {
+        assert!(BorshType::from_str("Option").is_err());
+        assert!(BorshType::from_str("BTreeMap<u8>").is_err());
+        assert!(BorshType::from_str("[u8; N]").is_err());
+        assert!(BorshType::from_str("fn(u8) -> u8").is_err());
+        assert!(BorshType::from_str("Vec<u8").is_err());
+    }
+
+    #[test]
+    fn collection_type_from_str() {
+        assert_eq!(
+            BorshType::from_str("HashMap<String, u64>").unwrap(),
+            BorshType::Map(Box::new(BorshType::String), Box::new(BorshType::U64))
+        );
+        assert_eq!(
+            BorshType::from_str("BTreeMap<String, u64>").unwrap(),
+            BorshType::from_str("HashMap<String, u64>").unwrap(),
+        );
+        assert_eq!(
+            BorshType::from_str("HashSet<Pubkey>").unwrap(),
+            BorshType::Set(Box::new(BorshType::Pubkey))
+        );
+        assert_eq!(
+            BorshType::from_str("BTreeSet<Option<u16>>").unwrap(),
+            BorshType::Set(Box::new(BorshType::Option(Box::new(BorshType::U16))))
+        );
+    }
+
+    #[test]
+    fn tuple_type_from_str() {
+        assert_eq!(BorshType::from_str("()").unwrap(), BorshType::Tuple(vec![]));
+        assert_eq!(
+            BorshType::from_str("(u8, Pubkey)").unwrap(),
+            BorshType::Tuple(vec![BorshType::U8, BorshType::Pubkey])
+        );
+        assert_eq!(
+            BorshType::from_str("(String, Vec<u64>)").unwrap(),
+            BorshType::Tuple(vec![
+                BorshType::String,
+                BorshType::Vec(Box::new(BorshType::U64))
+            ])
+        );
+        assert_eq!(
+            BorshType::from_str("Option<(u8, (i16, [u8; 2]))>").unwrap(),
+            BorshType::Option(Box::new(BorshType::Tuple(vec![
+                BorshType::U8,
+                BorshType::Tuple(vec![BorshType::I16, BorshType::FixedBytes(2)])
+            ])))
+        );
+        assert_eq!(
+            BorshType::from_str("(u32,)").unwrap(),
+            BorshType::Tuple(vec![BorshType::U32])
+        );
+        assert_eq!(BorshType::from_str("(u32)").unwrap(), BorshType::U32);
+    }
+
+    #[test]
+    fn generic_type_from_str() {
+        let ty = BorshType::from_str("Wrapper<Vec<u64>, Option<Pubkey>>").unwrap();
+        assert_eq!(
+            ty,
+            BorshType::Generic(
+                "Wrapper".to_owned(),
+                vec![
+                    BorshType::Vec(Box::new(BorshType::U64)),
+                    BorshType::Option(Box::new(BorshType::Pubkey)),
+                ]
+            )
+        );
+        assert_eq!(ty.mangled_name(), "WrapperVecU64_OptionPubkey");
+        assert_ne!(
+            BorshType::from_str("Pair<FooBar, Baz>")
+                .unwrap()
+                .mangled_name(),
+            BorshType::from_str("Pair<Foo, BarBaz>")
+                .unwrap()
+                .mangled_name()
+        );
+        assert_ne!(
+            BorshType::from_str("W<[[u8; 1]; 23]>")
+                .unwrap()
+                .mangled_name(),
+            BorshType::from_str("W<[[u8; 12]; 3]>")
+                .unwrap()
+                .mangled_name()
+        );
+        assert_eq!(
+            ty.to_class_type().unwrap(),
+            "Wrapper<BN[], PublicKeyBE | null>"
+        );
+        assert_eq!(ty.to_borsh_schema().unwrap(), "Wrapper");
+        assert_eq!(
+            BorshType::from_str("Wrapper<'a>").unwrap(),
+            BorshType::Custom("Wrapper".to_owned())
+        );
+
+        let mut bindings = HashMap::new();
+        bindings.insert("T".to_owned(), BorshType::U8);
+        let ty = BorshType::Vec(Box::new(BorshType::Generic(
+            "Wrapper".to_owned(),
+            vec![BorshType::TypeParam("T".to_owned())],
+        )));
+        assert_eq!(
+            ty.substitute(&bindings).monomorphize(),
+            BorshType::Vec(Box::new(BorshType::Custom("WrapperU8".to_owned())))
+        );
+    }
+
     #[test]
     fn simple_type_to_borsh() {
-        assert_eq!(BorshType::Bool.to_borsh_schema(), "'u8'");
-        assert_eq!(BorshType::U8.to_borsh_schema(), "'u8'");
-        assert_eq!(BorshType::U16.to_borsh_schema(), "'u16'");
-        assert_eq!(BorshType::U32.to_borsh_schema(), "'u32'");
-        assert_eq!(BorshType::U64.to_borsh_schema(), "'u64'");
-        assert_eq!(BorshType::U128.to_borsh_schema(), "'u128'");
-        assert_eq!(BorshType::String.to_borsh_schema(), "'string'");
-        assert_eq!(BorshType::Pubkey.to_borsh_schema(), "'publicKey'");
+        assert_eq!(BorshType::Bool.to_borsh_schema().unwrap(), "'u8'");
+        assert_eq!(BorshType::U8.to_borsh_schema().unwrap(), "'u8'");
+        assert_eq!(BorshType::U16.to_borsh_schema().unwrap(), "'u16'");
+        assert_eq!(BorshType::U32.to_borsh_schema().unwrap(), "'u32'");
+        assert_eq!(BorshType::U64.to_borsh_schema().unwrap(), "'u64'");
+        assert_eq!(BorshType::U128.to_borsh_schema().unwrap(), "'u128'");
+        assert_eq!(BorshType::I8.to_borsh_schema().unwrap(), "'i8'");
+        assert_eq!(BorshType::I16.to_borsh_schema().unwrap(), "'i16'");
+        assert_eq!(BorshType::I32.to_borsh_schema().unwrap(), "'i32'");
+        assert_eq!(BorshType::I64.to_borsh_schema().unwrap(), "'i64'");
+        assert_eq!(BorshType::I128.to_borsh_schema().unwrap(), "'i128'");
+        assert_eq!(BorshType::F32.to_borsh_schema().unwrap(), "'f32'");
+        assert_eq!(BorshType::F64.to_borsh_schema().unwrap(), "'f64'");
+        assert_eq!(BorshType::String.to_borsh_schema().unwrap(), "'string'");
+        assert_eq!(BorshType::Pubkey.to_borsh_schema().unwrap(), "PublicKeyBE");
     }
 
     #[test]
     fn complex_type_to_borsh() {
-        assert_eq!(BorshType::FixedBytes(32).to_borsh_schema(), "[32]",);
+        assert_eq!(BorshType::FixedBytes(32).to_borsh_schema().unwrap(), "[32]",);
         assert_eq!(
-            BorshType::Vec(Box::new(BorshType::U64)).to_borsh_schema(),
+            BorshType::Vec(Box::new(BorshType::U64))
+                .to_borsh_schema()
+                .unwrap(),
             "['u64']"
         );
         assert_eq!(
             BorshType::from_str("Option<Vec<OtherStruct>>")
                 .unwrap()
-                .to_borsh_schema(),
+                .to_borsh_schema()
+                .unwrap(),
             "{ kind: 'option', type: [OtherStruct] }"
         );
 
         assert_eq!(
             BorshType::from_str("[[Option<i32>; 2]; 4]")
                 .unwrap()
-                .to_borsh_schema(),
-            "[[{ kind: 'option', type: 'u32' }, 2], 4]"
+                .to_borsh_schema()
+                .unwrap(),
+            "[[{ kind: 'option', type: 'i32' }, 2], 4]"
         );
 
         assert_eq!(
             BorshType::from_str("BTreeMap<[u8; 32], Pubkey>")
                 .unwrap()
-                .to_borsh_schema(),
-            "{ kind: 'map', key: [32], value: 'publicKey' }"
+                .to_borsh_schema()
+                .unwrap(),
+            "{ kind: 'map', key: [32], value: PublicKeyBE }"
+        );
+
+        assert_eq!(
+            BorshType::from_str("HashSet<[u8; 32]>")
+                .unwrap()
+                .to_borsh_schema()
+                .unwrap(),
+            "[[32]]"
+        );
+
+        assert_eq!(
+            BorshType::from_str("(u8, Pubkey)")
+                .unwrap()
+                .to_borsh_schema()
+                .unwrap(),
+            "TupleU8_Pubkey"
+        );
+        assert_eq!(
+            BorshType::from_str("Option<[(u8, (i16, bool)); 2]>")
+                .unwrap()
+                .to_borsh_schema()
+                .unwrap(),
+            "{ kind: 'option', type: [TupleU8_TupleI16_Bool, 2] }"
+        );
+        assert_eq!(
+            BorshType::from_str("()")
+                .unwrap()
+                .to_borsh_schema()
+                .unwrap(),
+            "Tuple"
         );
     }
 
     #[test]
     fn types_to_ts() {
         let ty = BorshType::from_str("u64").unwrap();
-        assert_eq!(ty.to_class_type(), "BN");
+        assert_eq!(ty.to_class_type().unwrap(), "BN");
+        let ty = BorshType::from_str("i64").unwrap();
+        assert_eq!(ty.to_class_type().unwrap(), "BN");
+        let ty = BorshType::from_str("i16").unwrap();
+        assert_eq!(ty.to_class_type().unwrap(), "number");
+        let ty = BorshType::from_str("f64").unwrap();
+        assert_eq!(ty.to_class_type().unwrap(), "number");
         let ty = BorshType::from_str("Option<Vec<Pubkey>>").unwrap();
-        assert_eq!(ty.to_class_type(), "PublicKey[] | null");
+        assert_eq!(ty.to_class_type().unwrap(), "PublicKeyBE[] | null");
         let ty = BorshType::from_str("[bool; 5]").unwrap();
-        assert_eq!(ty.to_class_type(), "boolean[]");
+        assert_eq!(ty.to_class_type().unwrap(), "boolean[]");
+        let ty = BorshType::from_str("(u8, Pubkey)").unwrap();
+        assert_eq!(ty.to_class_type().unwrap(), "TupleU8_Pubkey");
+        let ty = BorshType::from_str("Vec<(String, Vec<u64>)>").unwrap();
+        assert_eq!(ty.to_class_type().unwrap(), "TupleString_VecU64[]");
+        let ty = BorshType::from_str("()").unwrap();
+        assert_eq!(ty.to_class_type().unwrap(), "Tuple");
+        let ty = BorshType::from_str("BTreeSet<String>").unwrap();
+        assert_eq!(ty.to_class_type().unwrap(), "string[]");
         let ty = BorshType::from_str("BTreeMap<[u8; 32], PublicKey>").unwrap();
-        assert_eq!(dbg!(ty.to_class_type()), "Map<[32], PublicKey>");
+        assert_eq!(dbg!(ty.to_class_type().unwrap()), "Map<[32], PublicKey>");
     }
 }
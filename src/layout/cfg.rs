@@ -0,0 +1,323 @@
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+
+use std::collections::HashSet;
+
+/// The active features and configuration options that `cfg` and `cfg_attr`
+/// attributes are evaluated with, e.g. the features the on-chain program is
+/// built with.
+///
+/// # Example
+///
+/// ```rust
+/// # use agsol_borsh_schema::CfgSet;
+/// let cfg = CfgSet::new()
+///     .with_feature("no-entrypoint")
+///     .with_value("target_os", "solana");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CfgSet {
+    features: HashSet<String>,
+    flags: HashSet<String>,
+    values: HashSet<(String, String)>,
+}
+
+impl CfgSet {
+    /// Creates a configuration without any active options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Activates a feature, i.e. `cfg(feature = "name")`.
+    pub fn with_feature(mut self, name: impl Into<String>) -> Self {
+        self.features.insert(name.into());
+        self
+    }
+
+    /// Activates a configuration flag, e.g. `cfg(test)`.
+    pub fn with_flag(mut self, name: impl Into<String>) -> Self {
+        self.flags.insert(name.into());
+        self
+    }
+
+    /// Activates a configuration option with a value, e.g.
+    /// `cfg(target_os = "solana")`.
+    pub fn with_value(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.values.insert((name.into(), value.into()));
+        self
+    }
+
+    /// Removes inactive items, `struct` fields and `enum` variants, and
+    /// replaces active `cfg_attr` attributes by the attributes they contain.
+    pub fn configure_items(&self, items: &mut Vec<syn::Item>) -> Result<(), anyhow::Error> {
+        let mut configured = Vec::with_capacity(items.len());
+        for mut item in items.drain(..) {
+            if let Some(attrs) = item_attrs(&mut item) {
+                if !self.configure_attrs(attrs)? {
+                    continue;
+                }
+            }
+            match &mut item {
+                syn::Item::Struct(item_struct) => self.configure_fields(&mut item_struct.fields)?,
+                syn::Item::Enum(item_enum) => {
+                    let mut variants = Punctuated::new();
+                    for mut variant in std::mem::take(&mut item_enum.variants) {
+                        if self.configure_attrs(&mut variant.attrs)? {
+                            self.configure_fields(&mut variant.fields)?;
+                            variants.push(variant);
+                        }
+                    }
+                    item_enum.variants = variants;
+                }
+                syn::Item::Mod(item_mod) => {
+                    if let Some((_, items)) = &mut item_mod.content {
+                        self.configure_items(items)?;
+                    }
+                }
+                _ => {}
+            }
+            configured.push(item);
+        }
+        *items = configured;
+        Ok(())
+    }
+
+    fn configure_fields(&self, fields: &mut syn::Fields) -> Result<(), anyhow::Error> {
+        let fields = match fields {
+            syn::Fields::Named(named) => &mut named.named,
+            syn::Fields::Unnamed(unnamed) => &mut unnamed.unnamed,
+            syn::Fields::Unit => return Ok(()),
+        };
+        let mut configured = Punctuated::new();
+        for mut field in std::mem::take(fields) {
+            if self.configure_attrs(&mut field.attrs)? {
+                configured.push(field);
+            }
+        }
+        *fields = configured;
+        Ok(())
+    }
+
+    /// Expands the `cfg_attr` attributes and returns `false` if a `cfg`
+    /// attribute is inactive.
+    fn configure_attrs(&self, attrs: &mut Vec<syn::Attribute>) -> Result<bool, anyhow::Error> {
+        let mut pending = std::mem::take(attrs);
+        pending.reverse();
+        while let Some(attr) = pending.pop() {
+            if attr.path.is_ident("cfg") {
+                let predicate = attr.parse_args::<syn::Meta>()?;
+                let active = self
+                    .evaluate(&predicate)
+                    .map_err(|err| syn::Error::new_spanned(&predicate, err))?;
+                if !active {
+                    return Ok(false);
+                }
+                attrs.push(attr);
+            } else if attr.path.is_ident("cfg_attr") {
+                let (predicate, expanded) = attr.parse_args_with(parse_cfg_attr)?;
+                let active = self
+                    .evaluate(&predicate)
+                    .map_err(|err| syn::Error::new_spanned(&predicate, err))?;
+                if active {
+                    // the expanded attributes may contain `cfg`s themselves
+                    pending.extend(expanded.into_iter().rev());
+                }
+            } else {
+                attrs.push(attr);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Evaluates a configuration predicate.
+    pub fn evaluate(&self, predicate: &syn::Meta) -> Result<bool, anyhow::Error> {
+        match predicate {
+            syn::Meta::Path(path) => Ok(path
+                .get_ident()
+                .is_some_and(|ident| self.flags.contains(&ident.to_string()))),
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(value),
+                ..
+            }) => {
+                let name = path
+                    .get_ident()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                if name == "feature" {
+                    Ok(self.features.contains(&value.value()))
+                } else {
+                    Ok(self.values.contains(&(name, value.value())))
+                }
+            }
+            syn::Meta::List(list) => {
+                let predicates = list
+                    .nested
+                    .iter()
+                    .map(|nested| match nested {
+                        syn::NestedMeta::Meta(meta) => self.evaluate(meta),
+                        syn::NestedMeta::Lit(_) => Err(unsupported_predicate(predicate)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if list.path.is_ident("all") {
+                    Ok(predicates.into_iter().all(|active| active))
+                } else if list.path.is_ident("any") {
+                    Ok(predicates.into_iter().any(|active| active))
+                } else if list.path.is_ident("not") && predicates.len() == 1 {
+                    Ok(!predicates[0])
+                } else {
+                    Err(unsupported_predicate(predicate))
+                }
+            }
+            _ => Err(unsupported_predicate(predicate)),
+        }
+    }
+}
+
+fn unsupported_predicate(predicate: &syn::Meta) -> anyhow::Error {
+    anyhow::anyhow!(
+        "unsupported cfg predicate `{}`",
+        quote::ToTokens::to_token_stream(predicate)
+    )
+}
+
+/// Parses the arguments of `cfg_attr(predicate, attr, ...)`.
+fn parse_cfg_attr(input: ParseStream) -> syn::Result<(syn::Meta, Vec<syn::Attribute>)> {
+    let predicate = input.parse::<syn::Meta>()?;
+    let mut attrs = Vec::new();
+    while !input.is_empty() {
+        input.parse::<syn::Token![,]>()?;
+        if input.is_empty() {
+            break;
+        }
+        let path = input.call(syn::Path::parse_mod_style)?;
+        let mut tokens = proc_macro2::TokenStream::new();
+        while !input.is_empty() && !input.peek(syn::Token![,]) {
+            tokens.extend(std::iter::once(input.parse::<proc_macro2::TokenTree>()?));
+        }
+        attrs.push(syn::Attribute {
+            pound_token: Default::default(),
+            style: syn::AttrStyle::Outer,
+            bracket_token: Default::default(),
+            path,
+            tokens,
+        });
+    }
+    Ok((predicate, attrs))
+}
+
+/// Returns the attributes of the items that may be gated by `cfg`.
+fn item_attrs(item: &mut syn::Item) -> Option<&mut Vec<syn::Attribute>> {
+    match item {
+        syn::Item::Const(item) => Some(&mut item.attrs),
+        syn::Item::Enum(item) => Some(&mut item.attrs),
+        syn::Item::Mod(item) => Some(&mut item.attrs),
+        syn::Item::Struct(item) => Some(&mut item.attrs),
+        syn::Item::Type(item) => Some(&mut item.attrs),
+        syn::Item::Use(item) => Some(&mut item.attrs),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn evaluate(cfg: &CfgSet, predicate: &str) -> bool {
+        cfg.evaluate(&syn::parse_str(predicate).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn predicate_evaluation() {
+        let cfg = CfgSet::new()
+            .with_feature("a")
+            .with_flag("test")
+            .with_value("target_os", "solana");
+        assert!(evaluate(&cfg, r#"feature = "a""#));
+        assert!(!evaluate(&cfg, r#"feature = "b""#));
+        assert!(evaluate(&cfg, "test"));
+        assert!(!evaluate(&cfg, "debug_assertions"));
+        assert!(evaluate(&cfg, r#"target_os = "solana""#));
+        assert!(!evaluate(&cfg, r#"target_os = "linux""#));
+        assert!(evaluate(&cfg, r#"all(feature = "a", test)"#));
+        assert!(!evaluate(&cfg, r#"all(feature = "a", feature = "b")"#));
+        assert!(evaluate(&cfg, "all()"));
+        assert!(evaluate(&cfg, r#"any(feature = "b", not(feature = "c"))"#));
+        assert!(!evaluate(&cfg, "any()"));
+        assert!(cfg
+            .evaluate(&syn::parse_str("not(test, test)").unwrap())
+            .is_err());
+        assert!(cfg
+            .evaluate(&syn::parse_str("maybe(test)").unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn item_configuration() {
+        let mut file = syn::parse_file(
+            r#"#[cfg(feature = "a")]
+            const N: usize = 1;
+            #[cfg(not(feature = "a"))]
+            const N: usize = 2;
+            #[cfg_attr(feature = "a", derive(BorshSchemaTS), cfg(test))]
+            struct Gated {
+                always: u8,
+                #[cfg(feature = "b")]
+                never: u8,
+                #[cfg_attr(all(), borsh_skip)]
+                skipped: u8,
+            }
+            enum Variants {
+                A,
+                #[cfg(feature = "b")]
+                B,
+                C(#[cfg(feature = "a")] u8, #[cfg(feature = "b")] u16),
+            }
+            #[cfg(test)]
+            mod tests {}
+            mod inner {
+                #[cfg(feature = "b")]
+                struct Inactive;
+            }"#,
+        )
+        .unwrap();
+        let cfg = CfgSet::new().with_feature("a");
+        cfg.configure_items(&mut file.items).unwrap();
+
+        let code = quote::ToTokens::to_token_stream(&file).to_string();
+        let expected = quote::quote! {
+            #[cfg(feature = "a")]
+            const N: usize = 1;
+            enum Variants {
+                A,
+                C(#[cfg(feature = "a")] u8)
+            }
+            mod inner {}
+        }
+        .to_string();
+        assert_eq!(code, expected);
+
+        let cfg = cfg.with_flag("test");
+        let mut file = syn::parse_file(
+            r#"#[cfg_attr(feature = "a", derive(BorshSchemaTS), cfg(test))]
+            struct Gated {
+                #[cfg_attr(all(), borsh_skip, alias([u8; 32]))]
+                skipped: u8,
+            }"#,
+        )
+        .unwrap();
+        cfg.configure_items(&mut file.items).unwrap();
+        let code = quote::ToTokens::to_token_stream(&file).to_string();
+        let expected = quote::quote! {
+            #[derive(BorshSchemaTS)]
+            #[cfg(test)]
+            struct Gated {
+                #[borsh_skip]
+                #[alias([u8; 32])]
+                skipped: u8
+            }
+        }
+        .to_string();
+        assert_eq!(code, expected);
+    }
+}
@@ -0,0 +1,158 @@
+use quote::ToTokens;
+
+use std::collections::HashMap;
+
+/// A `type` alias declared in the scanned source files.
+#[derive(Clone)]
+pub struct TypeAlias {
+    /// Generic type parameters of the alias with their optional defaults.
+    pub params: Vec<(String, Option<syn::Type>)>,
+    /// The aliased type.
+    pub ty: syn::Type,
+}
+
+impl TypeAlias {
+    fn from_item(item: &syn::ItemType) -> Self {
+        let params = item
+            .generics
+            .type_params()
+            .map(|param| (param.ident.to_string(), param.default.clone()))
+            .collect();
+        Self {
+            params,
+            ty: item.ty.as_ref().clone(),
+        }
+    }
+
+    fn same_definition(&self, other: &Self) -> bool {
+        self.ty.to_token_stream().to_string() == other.ty.to_token_stream().to_string()
+            && self.params.len() == other.params.len()
+    }
+}
+
+/// Definitions collected from every scanned source file that are needed for
+/// resolving field types, e.g. type aliases and constants used as array
+/// lengths.
+#[derive(Clone, Default)]
+pub struct Definitions {
+    aliases: HashMap<String, Vec<(Vec<String>, TypeAlias)>>,
+    constants: HashMap<String, Vec<syn::Expr>>,
+    layouts: HashMap<String, Vec<Vec<String>>>,
+}
+
+impl Definitions {
+    /// Collects the definitions found in the items of the module at
+    /// `module_path`, including the items of inline modules.
+    pub fn collect(&mut self, items: &[syn::Item], module_path: &[String]) {
+        for item in items {
+            match item {
+                syn::Item::Type(item_type) => {
+                    let alias = TypeAlias::from_item(item_type);
+                    let entry = self.aliases.entry(item_type.ident.to_string()).or_default();
+                    if !entry.iter().any(|(module, other)| {
+                        module == module_path && other.same_definition(&alias)
+                    }) {
+                        entry.push((module_path.to_vec(), alias));
+                    }
+                }
+                syn::Item::Const(item_const) => {
+                    let expr = item_const.expr.as_ref();
+                    let entry = self
+                        .constants
+                        .entry(item_const.ident.to_string())
+                        .or_default();
+                    let tokens = expr.to_token_stream().to_string();
+                    if !entry
+                        .iter()
+                        .any(|other| other.to_token_stream().to_string() == tokens)
+                    {
+                        entry.push(expr.clone());
+                    }
+                }
+                syn::Item::Mod(item_mod) => {
+                    if let Some((_, items)) = &item_mod.content {
+                        let mut module_path = module_path.to_vec();
+                        module_path.push(item_mod.ident.to_string());
+                        self.collect(items, &module_path);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Looks up a type alias by name in every module.
+    ///
+    /// Returns an error if different aliases with the same name have been
+    /// declared in the scanned files, because the parser can't tell which one
+    /// is meant.
+    pub fn alias(&self, name: &str) -> Result<Option<&TypeAlias>, anyhow::Error> {
+        let aliases = self.aliases.get(name).map_or(&[][..], Vec::as_slice);
+        unique_alias(name, aliases.iter().map(|(_, alias)| alias))
+    }
+
+    /// Looks up a type alias declared in the module at `module_path`.
+    pub fn module_alias(
+        &self,
+        name: &str,
+        module_path: &[String],
+    ) -> Result<Option<&TypeAlias>, anyhow::Error> {
+        let aliases = self.aliases.get(name).map_or(&[][..], Vec::as_slice);
+        unique_alias(
+            name,
+            aliases
+                .iter()
+                .filter(|(module, _)| module == module_path)
+                .map(|(_, alias)| alias),
+        )
+    }
+
+    /// Looks up the value expression of a constant by name.
+    ///
+    /// Returns an error if different constants with the same name have been
+    /// declared in the scanned files.
+    pub fn constant(&self, name: &str) -> Result<Option<&syn::Expr>, anyhow::Error> {
+        match self.constants.get(name).map(Vec::as_slice) {
+            None | Some([]) => Ok(None),
+            Some([expr]) => Ok(Some(expr)),
+            Some(_) => Err(anyhow::anyhow!(
+                "ambiguous constant `{}`, use the `alias` attribute to specify the type",
+                name
+            )),
+        }
+    }
+
+    /// Registers a layout declared in a module, so that references to it can
+    /// be told apart from layouts with the same name in other modules.
+    pub fn declare_layout(&mut self, name: &str, module_path: &[String]) {
+        let modules = self.layouts.entry(name.to_owned()).or_default();
+        if !modules.iter().any(|module| module == module_path) {
+            modules.push(module_path.to_vec());
+        }
+    }
+
+    /// Returns the paths of the modules that declare a layout with the given
+    /// name.
+    pub fn layout_modules(&self, name: &str) -> &[Vec<String>] {
+        self.layouts.get(name).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Returns the alias if all `aliases` have the same definition.
+fn unique_alias<'a>(
+    name: &str,
+    mut aliases: impl Iterator<Item = &'a TypeAlias>,
+) -> Result<Option<&'a TypeAlias>, anyhow::Error> {
+    let alias = match aliases.next() {
+        Some(alias) => alias,
+        None => return Ok(None),
+    };
+    if aliases.all(|other| other.same_definition(alias)) {
+        Ok(Some(alias))
+    } else {
+        Err(anyhow::anyhow!(
+            "ambiguous type alias `{}`, use the `alias` attribute to specify the type",
+            name
+        ))
+    }
+}
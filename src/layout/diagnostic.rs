@@ -0,0 +1,232 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A position in a scanned source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column number.
+    pub column: usize,
+    /// Number of characters the location spans on its line.
+    pub len: usize,
+}
+
+impl Location {
+    pub(crate) fn from_span(span: proc_macro2::Span) -> Self {
+        let (start, end) = (span.start(), span.end());
+        let len = if end.line == start.line && end.column > start.column {
+            end.column - start.column
+        } else {
+            1
+        };
+        Self {
+            line: start.line,
+            column: start.column + 1,
+            len,
+        }
+    }
+}
+
+/// An error found while generating the layouts, along with where it was found
+/// in the scanned sources.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub message: String,
+    /// The file the error is located in.
+    pub path: Option<PathBuf>,
+    /// Name of the `struct` or `enum` the error is located in.
+    pub item: Option<String>,
+    /// Name of the field the error is located in.
+    pub field: Option<String>,
+    pub location: Option<Location>,
+    /// The source line of `location`.
+    pub snippet: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            path: None,
+            item: None,
+            field: None,
+            location: None,
+            snippet: None,
+        }
+    }
+
+    pub fn spanned(message: impl Into<String>, span: proc_macro2::Span) -> Self {
+        Self {
+            location: Some(Location::from_span(span)),
+            ..Self::new(message)
+        }
+    }
+
+    /// Converts an error into diagnostics, keeping the locations of syntax
+    /// errors. The message includes the causes of the error.
+    pub fn from_error(err: anyhow::Error) -> Vec<Self> {
+        match err.downcast::<Diagnostics>() {
+            Ok(diagnostics) => diagnostics.0,
+            Err(err) => match err.downcast::<syn::Error>() {
+                Ok(err) => err
+                    .into_iter()
+                    .map(|err| Self::spanned(err.to_string(), err.span()))
+                    .collect(),
+                Err(err) => vec![Self::new(format!("{:#}", err))],
+            },
+        }
+    }
+
+    /// Sets the location of the error, unless it is already known.
+    pub fn at(mut self, span: proc_macro2::Span) -> Self {
+        self.location
+            .get_or_insert_with(|| Location::from_span(span));
+        self
+    }
+
+    /// Sets the item the error is located in, unless it is already known.
+    pub fn in_item(mut self, item: &str) -> Self {
+        self.item.get_or_insert_with(|| item.to_owned());
+        self
+    }
+
+    /// Sets the field the error is located in, unless it is already known.
+    pub fn in_field(mut self, field: &str) -> Self {
+        self.field.get_or_insert_with(|| field.to_owned());
+        self
+    }
+
+    /// Sets the file the error is located in and extracts the source line of
+    /// its location from `code`.
+    pub fn set_source(&mut self, path: Option<&Path>, code: &str) {
+        if self.path.is_some() {
+            return;
+        }
+        self.path = path.map(Path::to_path_buf);
+        if let Some(location) = &self.location {
+            self.snippet = code
+                .lines()
+                .nth(location.line.saturating_sub(1))
+                .map(str::to_owned);
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    /// Renders the error similarly to `rustc`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error: {}", self.message)?;
+        let gutter = self
+            .location
+            .as_ref()
+            .map_or(0, |location| location.line.to_string().len());
+        let pad = " ".repeat(gutter);
+        let path = self.path.as_ref().map(|path| path.display().to_string());
+        match (&path, &self.location) {
+            (Some(path), Some(location)) => write!(
+                f,
+                "\n{}--> {}:{}:{}",
+                pad, path, location.line, location.column
+            )?,
+            (None, Some(location)) => {
+                write!(f, "\n{}--> {}:{}", pad, location.line, location.column)?
+            }
+            (Some(path), None) => write!(f, "\n{}--> {}", pad, path)?,
+            (None, None) => {}
+        }
+        if let (Some(location), Some(snippet)) = (&self.location, &self.snippet) {
+            let marker = " ".repeat(location.column - 1) + &"^".repeat(location.len);
+            write!(f, "\n{} |", pad)?;
+            write!(f, "\n{} | {}", location.line, snippet)?;
+            write!(f, "\n{} | {}", pad, marker)?;
+        }
+        match (&self.item, &self.field) {
+            (Some(item), Some(field)) => {
+                write!(f, "\n{} = note: in field `{}` of `{}`", pad, field, item)
+            }
+            (Some(item), None) => write!(f, "\n{} = note: in `{}`", pad, item),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// All the errors found while generating the layouts.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl Diagnostics {
+    /// Returns an error if any diagnostics were collected.
+    pub fn into_result(self) -> Result<(), anyhow::Error> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.into())
+        }
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, diagnostic) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f, "\n")?;
+            }
+            write!(f, "{}", diagnostic)?;
+        }
+        if self.0.len() > 1 {
+            write!(
+                f,
+                "\n\nerror: aborting due to {} previous errors",
+                self.0.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diagnostic_rendering() {
+        let code = "struct Foo {\n    bar: BTreeMap<u8>,\n}";
+        let item_struct = syn::parse_str::<syn::ItemStruct>(code).unwrap();
+        let field = item_struct.fields.iter().next().unwrap();
+        let span = syn::spanned::Spanned::span(&field.ty);
+        let mut diagnostic = Diagnostic::from_error(anyhow::anyhow!("invalid BTreeMap"))
+            .remove(0)
+            .at(span)
+            .in_field("bar")
+            .in_item("Foo");
+        diagnostic.set_source(Some(Path::new("src/foo.rs")), code);
+        assert_eq!(
+            diagnostic.location,
+            Some(Location {
+                line: 2,
+                column: 10,
+                len: 12
+            })
+        );
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: invalid BTreeMap\n \
+            --> src/foo.rs:2:10\n  \
+            |\n\
+            2 |     bar: BTreeMap<u8>,\n  \
+            |          ^^^^^^^^^^^^\n  \
+            = note: in field `bar` of `Foo`"
+        );
+
+        let diagnostics = Diagnostics(vec![diagnostic, Diagnostic::new("other")]);
+        assert!(diagnostics
+            .to_string()
+            .ends_with("\n\nerror: other\n\nerror: aborting due to 2 previous errors"));
+    }
+}
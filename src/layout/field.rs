@@ -1,56 +1,140 @@
-use super::BorshType;
-use heck::MixedCase;
-use proc_macro2::TokenStream;
-use quote::ToTokens;
+use super::{doc_comments, BorshType, CodeStyle, EmitError, FieldNaming, TypeResolver};
+use heck::{MixedCase, SnakeCase};
 
 use std::str::FromStr;
 
 /// Represents a field in a TypeScript class and a borsh schema.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LayoutField {
-    name: String,
-    ty: BorshType,
+    /// The (TypeScript) name of the field.
+    pub name: String,
+    /// The name of the field in Rust, which is used instead of `name` with
+    /// other naming conventions than [`FieldNaming::CamelCase`]. Unnamed
+    /// fields are named `unnamed_{n}`, and the fields of `enum` layouts are
+    /// named after the layouts of the variants.
+    pub rust_name: String,
+    pub ty: BorshType,
+    /// Lines of the doc comments of the field, or of the variant for the
+    /// fields of `enum` layouts.
+    pub docs: Vec<String>,
 }
 
 impl LayoutField {
     /// Converts a [`Field`](syn::Field) type into a layout field by extracting
     /// its name and type.
-    pub fn from_tokens(field: &syn::Field, n: usize) -> Result<Self, anyhow::Error> {
-        let name = if let Some(field_name) = field.ident.as_ref() {
-            field_name.to_string().to_mixed_case()
-        } else {
-            format!("unnamed_{}", n)
+    pub fn from_tokens(
+        field: &syn::Field,
+        n: usize,
+        resolver: &TypeResolver,
+    ) -> Result<Self, anyhow::Error> {
+        let name = Self::field_name(field, n);
+        let rust_name = match field.ident.as_ref() {
+            Some(ident) => ident.to_string(),
+            None => name.clone(),
         };
-        let ty = if let Some(alias) = field.attrs.iter().find(|attr| attr.path.is_ident("alias")) {
-            BorshType::from_str(&alias.parse_args::<TokenStream>()?.to_string())?
+        let borsh_attrs = BorshAttributes::from_attrs(&field.attrs)?;
+        let ty = if borsh_attrs.skip {
+            BorshType::Skip
+        } else if let Some(alias) = field.attrs.iter().find(|attr| attr.path.is_ident("alias")) {
+            resolver.resolve(&alias.parse_args::<syn::Type>()?)?
         } else if field
             .attrs
             .iter()
             .any(|attr| attr.path.is_ident("schema_skip"))
         {
             BorshType::Skip
+        } else if let Some(serializer) = borsh_attrs.custom_serializer {
+            return Err(anyhow::anyhow!(
+                "field `{}` uses the custom borsh serializer `{}`, use the `alias` attribute to specify its wire type",
+                name,
+                serializer
+            ));
         } else {
-            BorshType::from_str(&field.ty.to_token_stream().to_string())?
+            resolver.resolve(&field.ty)?
         };
-        Ok(Self { name, ty })
+        Ok(Self {
+            name,
+            rust_name,
+            ty,
+            docs: doc_comments(&field.attrs),
+        })
+    }
+
+    /// Returns the name of the `n`th field in the TypeScript class.
+    pub fn field_name(field: &syn::Field, n: usize) -> String {
+        if let Some(field_name) = field.ident.as_ref() {
+            field_name.to_string().to_mixed_case()
+        } else {
+            format!("unnamed_{}", n)
+        }
     }
 
-    pub fn from_enum_variant(name_str: &str) -> Result<Self, anyhow::Error> {
-        let ty = BorshType::from_str(name_str)?;
+    /// Creates a field of an enum layout that refers to the layout of the
+    /// variant. Variants of generic enums are generic themselves.
+    pub fn from_enum_variant(name_str: &str, generics: &[String]) -> Result<Self, anyhow::Error> {
+        let mut ty = BorshType::from_str(name_str)?;
+        if !generics.is_empty() {
+            ty = BorshType::Generic(
+                name_str.to_owned(),
+                generics.iter().cloned().map(BorshType::TypeParam).collect(),
+            );
+        }
         Ok(Self {
             name: name_str.to_mixed_case(),
+            rust_name: name_str.to_owned(),
             ty,
+            docs: Vec::new(),
         })
     }
 
     /// Converts the field into a TypeScript class field representation.
-    pub fn to_class_field(&self) -> String {
-        format!("{}: {}", self.name, self.ty.to_class_type())
+    pub fn to_class_field(&self) -> Result<String, EmitError> {
+        Ok(format!("{}: {}", self.name, self.ty.to_class_type()?))
     }
 
     /// Converts the field into a borsh schema field representation.
-    pub fn to_borsh_schema(&self) -> String {
-        format!("['{}', {}]", self.name, self.ty.to_borsh_schema())
+    pub fn to_borsh_schema(&self) -> Result<String, EmitError> {
+        self.to_borsh_schema_with_style(&CodeStyle::default())
+    }
+
+    /// Converts the field into a borsh schema field representation using the
+    /// naming convention and the public key representation of a code style.
+    pub fn to_borsh_schema_with_style(&self, style: &CodeStyle) -> Result<String, EmitError> {
+        Ok(format!(
+            "['{}', {}]",
+            self.name_with(style.field_naming),
+            self.ty.to_borsh_schema_with_pubkey(style.pubkey)?
+        ))
+    }
+
+    /// Returns a copy of the field with a different type.
+    pub fn with_ty(&self, ty: BorshType) -> Self {
+        Self {
+            name: self.name.clone(),
+            rust_name: self.rust_name.clone(),
+            ty,
+            docs: self.docs.clone(),
+        }
+    }
+
+    /// Returns the (TypeScript) name of the field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name of the field using a naming convention.
+    pub fn name_with(&self, naming: FieldNaming) -> String {
+        match naming {
+            FieldNaming::CamelCase => self.name.clone(),
+            FieldNaming::SnakeCase => self.rust_name.to_snake_case(),
+            FieldNaming::Preserve => self.rust_name.clone(),
+        }
+    }
+
+    /// Returns the borsh type of the field.
+    pub fn ty(&self) -> &BorshType {
+        &self.ty
     }
 
     pub fn should_skip(&self) -> bool {
@@ -58,9 +142,52 @@ impl LayoutField {
     }
 }
 
+/// Field attributes of `borsh-rs` that change how the field is serialized.
+#[derive(Default)]
+struct BorshAttributes {
+    /// `#[borsh_skip]` (0.9) or `#[borsh(skip)]` (1.x).
+    skip: bool,
+    /// `#[borsh(serialize_with = "..")]` or `#[borsh(deserialize_with = "..")]`.
+    custom_serializer: Option<String>,
+}
+
+impl BorshAttributes {
+    fn from_attrs(attrs: &[syn::Attribute]) -> Result<Self, anyhow::Error> {
+        let mut borsh_attrs = Self::default();
+        for attr in attrs {
+            if attr.path.is_ident("borsh_skip") {
+                borsh_attrs.skip = true;
+            } else if attr.path.is_ident("borsh") {
+                let nested = match attr.parse_meta()? {
+                    syn::Meta::List(list) => list.nested,
+                    _ => continue,
+                };
+                for meta in nested {
+                    match meta {
+                        syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("skip") => {
+                            borsh_attrs.skip = true;
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(name_value))
+                            if name_value.path.is_ident("serialize_with")
+                                || name_value.path.is_ident("deserialize_with") =>
+                        {
+                            if let syn::Lit::Str(serializer) = &name_value.lit {
+                                borsh_attrs.custom_serializer = Some(serializer.value());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(borsh_attrs)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::layout::Definitions;
     use proc_macro2::{Span, TokenStream};
     use syn::token::Colon;
     use syn::{Ident, Type, Visibility};
@@ -77,9 +204,14 @@ mod test {
             ty: Type::Verbatim(TokenStream::from_str("u8").unwrap()),
         };
 
-        let field = LayoutField::from_tokens(&syn_field, 0).unwrap();
+        let field =
+            LayoutField::from_tokens(&syn_field, 0, &TypeResolver::new(&Definitions::default()))
+                .unwrap();
 
         assert_eq!(field.name, "randomField");
+        assert_eq!(field.rust_name, "random_field");
+        assert_eq!(field.name_with(FieldNaming::SnakeCase), "random_field");
+        assert_eq!(field.name_with(FieldNaming::Preserve), "random_field");
         assert_eq!(field.ty, BorshType::U8);
     }
 
@@ -95,7 +227,9 @@ mod test {
             ty: syn::parse_str("[Option<Pubkey>; 3]").unwrap(),
         };
 
-        let field = LayoutField::from_tokens(&syn_field, 0).unwrap();
+        let field =
+            LayoutField::from_tokens(&syn_field, 0, &TypeResolver::new(&Definitions::default()))
+                .unwrap();
 
         assert_eq!(field.name, "optionalAccounts");
         assert_eq!(
@@ -104,39 +238,124 @@ mod test {
         );
     }
 
+    #[test]
+    fn alias_field_construction() {
+        let item_struct = syn::parse_str::<syn::ItemStruct>(
+            "struct Foo {
+                #[alias(std::collections::BTreeMap<Vec<(u8, u8)>, u8>)]
+                some_alias: SomeAlias,
+            }",
+        )
+        .unwrap();
+
+        let field = LayoutField::from_tokens(
+            item_struct.fields.iter().next().unwrap(),
+            0,
+            &TypeResolver::new(&Definitions::default()),
+        )
+        .unwrap();
+
+        assert_eq!(field.name, "someAlias");
+        assert_eq!(
+            field.ty,
+            BorshType::Map(
+                Box::new(BorshType::Vec(Box::new(BorshType::Tuple(vec![
+                    BorshType::U8,
+                    BorshType::U8
+                ])))),
+                Box::new(BorshType::U8)
+            )
+        );
+    }
+
+    #[test]
+    fn borsh_attribute_field_construction() {
+        let item_struct = syn::parse_str::<syn::ItemStruct>(
+            r#"struct Foo {
+                #[borsh_skip]
+                legacy_skip: Cache,
+                #[borsh(skip)]
+                skip: Cache,
+                #[borsh(bound(serialize = "T: BorshSerialize"), skip)]
+                bounded_skip: Cache,
+                #[borsh(serialize_with = "ser::custom")]
+                custom: Custom,
+                #[alias(u64)]
+                #[borsh(serialize_with = "ser::custom", deserialize_with = "de::custom")]
+                aliased_custom: Custom,
+                #[borsh(bound(deserialize = "T: Default"))]
+                bounded: u8,
+            }"#,
+        )
+        .unwrap();
+        let definitions = Definitions::default();
+        let resolver = TypeResolver::new(&definitions);
+        let fields = item_struct
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| LayoutField::from_tokens(field, i, &resolver))
+            .collect::<Vec<_>>();
+
+        assert!(fields[0].as_ref().unwrap().should_skip());
+        assert!(fields[1].as_ref().unwrap().should_skip());
+        assert!(fields[2].as_ref().unwrap().should_skip());
+        let err = fields[3].as_ref().unwrap_err().to_string();
+        assert!(err.contains("`custom`"));
+        assert!(err.contains("`ser::custom`"));
+        assert_eq!(fields[4].as_ref().unwrap().ty, BorshType::U64);
+        assert_eq!(fields[5].as_ref().unwrap().ty, BorshType::U8);
+    }
+
     #[test]
     fn simple_field_to_borsh_schema() {
         let field = LayoutField {
             name: "someRandomString".to_owned(),
+            rust_name: "some_random_string".to_owned(),
             ty: BorshType::String,
+            docs: Vec::new(),
         };
 
-        assert_eq!(field.to_borsh_schema(), "['someRandomString', 'string']");
+        assert_eq!(
+            field.to_borsh_schema().unwrap(),
+            "['someRandomString', 'string']"
+        );
 
         let field = LayoutField {
             name: "myCustomType".to_owned(),
+            rust_name: "my_custom_type".to_owned(),
             ty: BorshType::Custom("aCustomType".to_owned()),
+            docs: Vec::new(),
         };
 
-        assert_eq!(field.to_borsh_schema(), "['myCustomType', aCustomType]");
+        assert_eq!(
+            field.to_borsh_schema().unwrap(),
+            "['myCustomType', aCustomType]"
+        );
     }
 
     #[test]
     fn field_to_ts_class_field() {
         let field = LayoutField {
             name: "fieldAlpha".to_owned(),
+            rust_name: "field_alpha".to_owned(),
             ty: BorshType::U64,
+            docs: Vec::new(),
         };
-        assert_eq!(field.to_class_field(), "fieldAlpha: BN");
+        assert_eq!(field.to_class_field().unwrap(), "fieldAlpha: BN");
         let field = LayoutField {
             name: "fieldBeta".to_owned(),
+            rust_name: "field_beta".to_owned(),
             ty: BorshType::Vec(Box::new(BorshType::String)),
+            docs: Vec::new(),
         };
-        assert_eq!(field.to_class_field(), "fieldBeta: string[]");
+        assert_eq!(field.to_class_field().unwrap(), "fieldBeta: string[]");
         let field = LayoutField {
             name: "fieldGamma".to_owned(),
+            rust_name: "field_gamma".to_owned(),
             ty: BorshType::Option(Box::new(BorshType::FixedBytes(32))),
+            docs: Vec::new(),
         };
-        assert_eq!(field.to_class_field(), "fieldGamma: [32] | null");
+        assert_eq!(field.to_class_field().unwrap(), "fieldGamma: [32] | null");
     }
 }
@@ -1,11 +1,30 @@
 mod borsh_type;
+mod cfg;
+mod definitions;
+mod diagnostic;
 mod field;
-pub use borsh_type::BorshType;
+mod resolver;
+mod scope;
+mod style;
+pub use borsh_type::{BorshType, EmitError};
+pub use cfg::CfgSet;
+pub use definitions::{Definitions, TypeAlias};
+pub use diagnostic::{Diagnostic, Diagnostics, Location};
 pub use field::LayoutField;
+pub use resolver::TypeResolver;
+pub use scope::{CrateScope, ModuleScope};
+pub use style::{CodeStyle, FieldNaming, Nullability, PubkeyStrategy};
+
+use heck::{CamelCase, MixedCase};
+use syn::spanned::Spanned;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 
 /// Indicates whether the layout should be generated for a `struct` or an
 /// `enum` type.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Kind {
     Enum,
     Struct,
@@ -13,11 +32,34 @@ pub enum Kind {
 
 /// The layout of a Rust data structure that is straghtforward to convert into
 /// a TypeScript class and the respective borsh schema.
-#[derive(Debug)]
+///
+/// The fields of an `enum` layout are its variants, each referring to the
+/// layout of the variant, which is a `struct` layout named after the `enum`
+/// and the variant, e.g. `InstructionInit`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Layout {
     pub name: String,
     pub kind: Kind,
     pub fields: Vec<LayoutField>,
+    /// Generic type parameters of the data structure.
+    pub generics: Vec<String>,
+    /// The serialized discriminants of the variants of an `enum` in the order
+    /// of `fields`. Empty for `struct`s.
+    pub discriminants: Vec<u8>,
+    /// Path of the module the data structure is declared in. When layouts
+    /// are collected from multiple crates, the first segment is the name of
+    /// the crate.
+    pub module_path: Vec<String>,
+    /// Name of the crate the data structure is declared in, if layouts are
+    /// collected from multiple crates.
+    pub crate_name: Option<String>,
+    /// The file the data structure is declared in, if it was read from one.
+    pub source: Option<PathBuf>,
+    /// Location of the name of the data structure in `source`.
+    pub location: Option<Location>,
+    /// Lines of the doc comments of the data structure.
+    pub docs: Vec<String>,
 }
 
 impl Layout {
@@ -25,49 +67,229 @@ impl Layout {
     pub fn from_tokens(
         name: &str,
         fields: &mut dyn Iterator<Item = &syn::Field>,
+        generics: &syn::Generics,
+        resolver: &TypeResolver,
     ) -> Result<Self, anyhow::Error> {
-        let fields = fields
-            .enumerate()
-            .map(|(i, x)| LayoutField::from_tokens(x, i))
-            .collect::<Result<Vec<_>, _>>()?;
+        let const_params = generics
+            .const_params()
+            .map(|param| param.ident.to_string())
+            .collect::<Vec<_>>();
+        let generics = generics
+            .type_params()
+            .map(|param| param.ident.to_string())
+            .collect::<Vec<_>>();
+        let resolver = resolver
+            .with_type_params(&generics)
+            .with_const_params(&const_params);
+        // errors are collected from every field
+        let mut layout_fields = Vec::new();
+        let mut diagnostics = Vec::new();
+        for (i, field) in fields.enumerate() {
+            match LayoutField::from_tokens(field, i, &resolver) {
+                Ok(layout_field) => layout_fields.push(layout_field),
+                Err(err) => {
+                    let field_name = LayoutField::field_name(field, i);
+                    diagnostics.extend(Diagnostic::from_error(err).into_iter().map(|diagnostic| {
+                        diagnostic
+                            .at(field.ty.span())
+                            .in_field(&field_name)
+                            .in_item(name)
+                    }));
+                }
+            }
+        }
+        Diagnostics(diagnostics).into_result()?;
         Ok(Self {
             name: name.to_string(),
             kind: Kind::Struct,
-            fields,
+            fields: layout_fields,
+            generics,
+            discriminants: Vec::new(),
+            module_path: Vec::new(),
+            crate_name: None,
+            source: None,
+            location: None,
+            docs: Vec::new(),
         })
     }
 
+    /// Returns `true` if the layout has generic type parameters.
+    pub fn is_generic(&self) -> bool {
+        !self.generics.is_empty()
+    }
+
+    /// Returns the name of the TypeScript class, including generic type
+    /// parameters.
+    fn class_name(&self) -> String {
+        if self.is_generic() {
+            format!("{}<{}>", self.name, self.generics.join(", "))
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Adds the field that can't be emitted to an emitter error.
+    fn emit_error(&self, field: &LayoutField, err: EmitError) -> anyhow::Error {
+        anyhow::Error::new(err).context(format!(
+            "can't emit field `{}` of `{}`",
+            field.name(),
+            self.name
+        ))
+    }
+
     /// Converts the layout into a TypeScript class.
-    pub fn to_ts_class(&self) -> String {
+    pub fn to_ts_class(&self) -> Result<String, anyhow::Error> {
+        self.to_ts_class_with_style(&CodeStyle::default())
+    }
+
+    /// Converts the layout into a TypeScript class in a custom code style.
+    pub fn to_ts_class_with_style(&self, style: &CodeStyle) -> Result<String, anyhow::Error> {
         let class_fields = self
             .fields
             .iter()
             .filter(|field| !field.should_skip())
-            .map(|field| String::from("\n  ") + &field.to_class_field() + " | undefined;")
-            .collect::<String>();
-        format!(
-            r#"export class {} extends {:?} {{{}
+            .map(|field| {
+                let class_type = field
+                    .ty()
+                    .to_class_type_with_pubkey(style.pubkey)
+                    .map_err(|err| self.emit_error(field, err))?;
+                let declaration = style
+                    .nullability
+                    .declare(&field.name_with(style.field_naming), &class_type);
+                Ok(String::from("\n") + &style.indent(1) + &declaration + ";")
+            })
+            .collect::<Result<String, anyhow::Error>>()?;
+        Ok(format!(
+            r#"export class {} extends {:?} {{{}{}
 }};
 
 "#,
-            self.name, self.kind, class_fields
+            self.class_name(),
+            self.kind,
+            class_fields,
+            self.to_ts_validation(style)
+        ))
+    }
+
+    /// Returns `true` if any of the fields contains a floating point number.
+    pub fn has_float_fields(&self) -> bool {
+        self.fields
+            .iter()
+            .any(|field| !field.should_skip() && field.ty().contains_float())
+    }
+
+    /// Generates a constructor that rejects `NaN` values in floating point
+    /// fields, the same way `borsh-rs` does.
+    fn to_ts_validation(&self, style: &CodeStyle) -> String {
+        if !self.has_float_fields() {
+            return String::new();
+        }
+        let checks = self
+            .fields
+            .iter()
+            .filter(|field| !field.should_skip() && field.ty().contains_float())
+            .map(|field| {
+                format!(
+                    "\n{1}rejectNaN(this.{0}, '{0}');",
+                    field.name_with(style.field_naming),
+                    style.indent(2)
+                )
+            })
+            .collect::<String>();
+        format!(
+            r#"
+
+{0}constructor(properties: any) {{
+{1}super(properties);{2}
+{0}}}"#,
+            style.indent(1),
+            style.indent(2),
+            checks
         )
     }
 
-    /// Converts the layout into a borsh schema.
-    pub fn to_borsh_schema(&self) -> String {
-        let first_line = match self.kind {
+    fn schema_first_line(&self) -> &'static str {
+        match self.kind {
             Kind::Struct => "kind: 'struct', fields:",
             Kind::Enum => "kind: 'enum', field: 'enum', values:",
-        };
-        let borsh_schema_fields = self
+        }
+    }
+
+    /// Returns `true` if the variants of an `enum` are serialized by their
+    /// position, i.e. the discriminants are contiguous from zero.
+    fn has_positional_discriminants(&self) -> bool {
+        self.discriminants
+            .iter()
+            .enumerate()
+            .all(|(i, discriminant)| i == *discriminant as usize)
+    }
+
+    /// Returns the borsh schema of every field of a `struct`, or every variant
+    /// of an `enum`.
+    ///
+    /// `borsh-js` reads the variant at the position of the discriminant, so the
+    /// unused discriminants of non-contiguous `enum`s are filled with
+    /// placeholders that fail to deserialize.
+    fn schema_entries(&self, style: &CodeStyle) -> Result<Vec<String>, anyhow::Error> {
+        let entries = self
             .fields
             .iter()
             .filter(|field| !field.should_skip())
-            .map(|field| String::from("\n\t\t\t") + &field.to_borsh_schema() + ",")
+            .map(|field| {
+                field
+                    .to_borsh_schema_with_style(style)
+                    .map_err(|err| self.emit_error(field, err))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if self.has_positional_discriminants() {
+            return Ok(entries);
+        }
+        let len = self
+            .discriminants
+            .iter()
+            .max()
+            .map_or(0, |max| *max as usize + 1);
+        let mut placeholders = (0..len)
+            .map(|i| format!("['unused{}', 'unused']", i))
+            .collect::<Vec<_>>();
+        for (entry, discriminant) in entries.into_iter().zip(&self.discriminants) {
+            placeholders[*discriminant as usize] = entry;
+        }
+        Ok(placeholders)
+    }
+
+    /// Converts the layout into a borsh schema.
+    pub fn to_borsh_schema(&self) -> Result<String, anyhow::Error> {
+        self.to_borsh_schema_with_style(&CodeStyle::default())
+    }
+
+    /// Converts the layout into a borsh schema using the naming convention,
+    /// the public key representation and the indentation of a code style.
+    /// The default indentation keeps the format of [`Layout::to_borsh_schema`].
+    pub fn to_borsh_schema_with_style(&self, style: &CodeStyle) -> Result<String, anyhow::Error> {
+        let first_line = self.schema_first_line();
+        let entries = self.schema_entries(style)?;
+        if style.indent != CodeStyle::default().indent {
+            let borsh_schema_fields = entries
+                .into_iter()
+                .map(|entry| String::from("\n") + &style.indent(4) + &entry + ",")
+                .collect::<String>();
+            return Ok(format!(
+                "\n{3}[\n{4}{0},\n{4}{{\n{5}{1} [{2}\n{5}],\n{4}}},\n{3}],",
+                self.name,
+                first_line,
+                borsh_schema_fields,
+                style.indent(1),
+                style.indent(2),
+                style.indent(3),
+            ));
+        }
+        let borsh_schema_fields = entries
+            .into_iter()
+            .map(|entry| String::from("\n\t\t\t") + &entry + ",")
             .collect::<String>();
         // NOTE don't change this string (tabs are included in the output string)
-        format!(
+        Ok(format!(
             r#"
     [
             {},
@@ -77,6 +299,555 @@ impl Layout {
             }},
     ],"#,
             self.name, first_line, borsh_schema_fields,
-        )
+        ))
+    }
+
+    /// Converts a generic layout into a function that takes the schemas of
+    /// the generic type arguments and returns the borsh schema of the
+    /// instantiated layout.
+    pub fn to_schema_factory(&self) -> Result<String, anyhow::Error> {
+        self.to_schema_factory_with_style(&CodeStyle::default())
+    }
+
+    /// Converts a generic layout into a schema factory in a custom code
+    /// style, see [`Layout::to_schema_factory`].
+    pub fn to_schema_factory_with_style(&self, style: &CodeStyle) -> Result<String, anyhow::Error> {
+        let params = self
+            .generics
+            .iter()
+            .map(|param| format!("{}: any", param))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let borsh_schema_fields = self
+            .schema_entries(style)?
+            .into_iter()
+            .map(|entry| String::from("\n") + &style.indent(2) + &entry + ",")
+            .collect::<String>();
+        Ok(format!(
+            r#"export const {}Schema = ({}): any => ({{
+{4}{} [{}
+{4}],
+}});
+
+"#,
+            self.name.to_mixed_case(),
+            params,
+            self.schema_first_line(),
+            borsh_schema_fields,
+            style.indent(1),
+        ))
+    }
+}
+
+/// A generic instantiation found in a layout, the crate of that layout and
+/// the number of instantiations it is nested in.
+type Instance = (String, Vec<BorshType>, Option<String>, usize);
+
+/// Instantiations nested deeper than this are considered to be recursive,
+/// e.g. `struct W<T> { inner: Option<Box<W<Vec<T>>>> }` instantiates
+/// `W<Vec<T>>`, which instantiates `W<Vec<Vec<T>>>` and so on.
+const MAX_INSTANCE_DEPTH: usize = 64;
+
+/// The maximum number of layouts generated by monomorphization, which stops
+/// recursive instantiations that branch before reaching
+/// [`MAX_INSTANCE_DEPTH`].
+const MAX_INSTANCES: usize = 10_000;
+
+/// Generates a separate layout for every instantiation of a generic layout,
+/// e.g. `Wrapper<u64>` becomes `WrapperU64`. Generic layouts are replaced by
+/// their instantiations, so generic layouts that are never instantiated are
+/// omitted. Instantiations belong to the crate of the layout that uses them
+/// first.
+///
+/// The names of the instantiations are given by [`BorshType::mangled_name`],
+/// it's an error if one of them is taken by another instantiation or layout.
+pub fn monomorphize(layouts: &[Layout]) -> Result<Vec<Layout>, anyhow::Error> {
+    let templates = layouts
+        .iter()
+        .filter(|layout| layout.is_generic())
+        .map(|layout| (layout.name.as_str(), layout))
+        .collect::<HashMap<_, _>>();
+
+    let mut instances = VecDeque::new();
+    let mut monomorphized = Vec::new();
+    for layout in layouts.iter().filter(|layout| !layout.is_generic()) {
+        monomorphized.push(layout.instantiate(
+            &HashMap::new(),
+            layout.crate_name.clone(),
+            0,
+            &mut instances,
+        ));
+    }
+
+    let names = layouts
+        .iter()
+        .filter(|layout| !layout.is_generic())
+        .map(|layout| layout.name.as_str())
+        .collect::<HashSet<_>>();
+    let mut generated = HashMap::<String, BorshType>::new();
+    while let Some((name, args, crate_name, depth)) = instances.pop_front() {
+        let template = match templates.get(name.as_str()) {
+            Some(template) => template,
+            // not a layout, leave the reference as it is
+            None => continue,
+        };
+        let instance = BorshType::Generic(name.clone(), args.clone());
+        let mangled_name = instance.mangled_name();
+        if let Some(generated_instance) = generated.get(&mangled_name) {
+            if *generated_instance == instance {
+                continue;
+            }
+            return Err(anyhow::anyhow!(
+                "can't emit an instantiation of `{}` as `{}`, because another instantiation is emitted with that name",
+                name,
+                mangled_name
+            ));
+        }
+        if names.contains(mangled_name.as_str()) {
+            return Err(anyhow::anyhow!(
+                "can't emit an instantiation of `{}` as `{}`, because a layout with that name already exists",
+                name,
+                mangled_name
+            ));
+        }
+        generated.insert(mangled_name.clone(), instance);
+        if depth > MAX_INSTANCE_DEPTH || generated.len() > MAX_INSTANCES {
+            return Err(anyhow::anyhow!(
+                "the instantiations of `{}` are recursive, the type arguments grow with every instantiation",
+                name
+            ));
+        }
+        if args.len() != template.generics.len() {
+            return Err(anyhow::anyhow!(
+                "`{}` expects {} generic type argument(s), found {}",
+                name,
+                template.generics.len(),
+                args.len()
+            ));
+        }
+        let bindings = template
+            .generics
+            .iter()
+            .cloned()
+            .zip(args)
+            .collect::<HashMap<_, _>>();
+        let mut layout = template.instantiate(&bindings, crate_name, depth, &mut instances);
+        layout.name = mangled_name;
+        monomorphized.push(layout);
+    }
+    Ok(monomorphized)
+}
+
+/// Replaces the tuples in the fields of the layouts by references to `struct`
+/// layouts with a field per element, which are appended to the layouts, see
+/// [`BorshType::Tuple`]. Tuple layouts belong to the module and the crate of
+/// the layout that uses them first.
+///
+/// Tuples can only be lifted once their generic type parameters are bound,
+/// i.e. after [`monomorphize`].
+pub fn lift_tuples(layouts: &[Layout]) -> Result<Vec<Layout>, anyhow::Error> {
+    let names = layouts
+        .iter()
+        .map(|layout| layout.name.as_str())
+        .collect::<HashSet<_>>();
+    let mut lifted = Vec::with_capacity(layouts.len());
+    let mut tuple_layouts = Vec::new();
+    let mut tuple_types = HashMap::<String, BorshType>::new();
+    for layout in layouts {
+        for field in &layout.fields {
+            let mut tuples = Vec::new();
+            field.ty().tuples(&mut tuples);
+            for tuple in tuples {
+                let name = tuple.mangled_name();
+                if let Some(lifted_tuple) = tuple_types.get(&name) {
+                    if *lifted_tuple == tuple {
+                        continue;
+                    }
+                    return Err(anyhow::anyhow!(
+                        "can't emit the tuple in `{}.{}`, because another tuple is emitted as `{}`",
+                        layout.name,
+                        field.name(),
+                        name
+                    ));
+                }
+                if names.contains(name.as_str()) {
+                    return Err(anyhow::anyhow!(
+                        "can't emit the tuple in `{}.{}` as `{}`, because a layout with that name already exists",
+                        layout.name,
+                        field.name(),
+                        name
+                    ));
+                }
+                if tuple.contains_type_params() {
+                    return Err(anyhow::anyhow!(
+                        "the tuple in `{}.{}` depends on generic type parameters, which requires `GenericsMode::Monomorphize`",
+                        layout.name,
+                        field.name()
+                    ));
+                }
+                if tuple.contains_skip() {
+                    return Err(layout.emit_error(field, EmitError::NestedSkip));
+                }
+                let elems = match &tuple {
+                    BorshType::Tuple(elems) => elems,
+                    _ => continue,
+                };
+                let fields = elems
+                    .iter()
+                    .enumerate()
+                    .map(|(i, elem)| LayoutField {
+                        name: format!("unnamed_{}", i),
+                        rust_name: format!("unnamed_{}", i),
+                        ty: elem.lift_tuples(),
+                        docs: Vec::new(),
+                    })
+                    .collect();
+                tuple_layouts.push(Layout {
+                    name: name.clone(),
+                    kind: Kind::Struct,
+                    fields,
+                    generics: Vec::new(),
+                    discriminants: Vec::new(),
+                    module_path: layout.module_path.clone(),
+                    crate_name: layout.crate_name.clone(),
+                    source: layout.source.clone(),
+                    location: None,
+                    docs: Vec::new(),
+                });
+                tuple_types.insert(name, tuple);
+            }
+        }
+        let mut layout = layout.clone();
+        layout.fields = layout
+            .fields
+            .iter()
+            .map(|field| field.with_ty(field.ty().lift_tuples()))
+            .collect();
+        lifted.push(layout);
+    }
+    lifted.append(&mut tuple_layouts);
+    Ok(lifted)
+}
+
+/// Renames layouts with the same name declared in different modules by
+/// prefixing their names with their module path, e.g. `state::Config` becomes
+/// `StateConfig`.
+///
+/// References to a renamed layout are resolved to the one declared in the
+/// module of the referring layout or the closest of its parent modules.
+/// Layouts with the same name declared in the same module are an error.
+pub fn disambiguate(layouts: &mut [Layout]) -> Result<(), anyhow::Error> {
+    let mut declared = HashMap::<String, Vec<Vec<String>>>::new();
+    let mut sources = HashMap::<(&[String], &str), String>::new();
+    for layout in layouts.iter() {
+        let source = match &layout.source {
+            Some(path) => format!("`{}`", path.display()),
+            None => "unknown file".to_owned(),
+        };
+        if let Some(first) = sources.insert((&layout.module_path, &layout.name), source.clone()) {
+            return Err(anyhow::anyhow!(
+                "`{}` is declared more than once, in {} and {}",
+                display_path(&layout.module_path, &layout.name),
+                first,
+                source
+            ));
+        }
+        let paths = declared.entry(layout.name.clone()).or_default();
+        if !paths.contains(&layout.module_path) {
+            paths.push(layout.module_path.clone());
+        }
+    }
+    let colliding = declared
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect::<HashMap<_, _>>();
+    if colliding.is_empty() {
+        return Ok(());
+    }
+
+    let names = layouts
+        .iter()
+        .map(|layout| layout.name.as_str())
+        .collect::<HashSet<_>>();
+    for (name, paths) in &colliding {
+        for path in paths.iter().filter(|path| !path.is_empty()) {
+            let qualified = qualified_name(path, name);
+            if names.contains(qualified.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "can't rename `{}` to `{}`, because a layout with that name already exists",
+                    display_path(path, name),
+                    qualified
+                ));
+            }
+        }
+    }
+
+    let mut renamed = Vec::with_capacity(layouts.len());
+    for layout in layouts.iter() {
+        let mut renames = HashMap::new();
+        for (name, paths) in &colliding {
+            let closest = (0..=layout.module_path.len())
+                .rev()
+                .map(|len| &layout.module_path[..len])
+                .find(|prefix| paths.iter().any(|path| path == prefix));
+            if let Some(path) = closest {
+                renames.insert(name.clone(), qualified_name(path, name));
+            }
+        }
+        let mut referenced = Vec::new();
+        for field in &layout.fields {
+            field.ty().referenced_layouts(&mut referenced);
+        }
+        if let Some(name) = referenced
+            .iter()
+            .find(|name| colliding.contains_key(*name) && !renames.contains_key(*name))
+        {
+            return Err(anyhow::anyhow!(
+                "ambiguous reference to `{}` in `{}`, it is declared as {}",
+                name,
+                display_path(&layout.module_path, &layout.name),
+                colliding[name]
+                    .iter()
+                    .map(|path| format!("`{}`", display_path(path, name)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        let fields = layout
+            .fields
+            .iter()
+            .map(|field| field.with_ty(field.ty().rename_layouts(&renames)))
+            .collect::<Vec<_>>();
+        let name = if colliding.contains_key(&layout.name) {
+            qualified_name(&layout.module_path, &layout.name)
+        } else {
+            layout.name.clone()
+        };
+        renamed.push((name, fields));
+    }
+    for (layout, (name, fields)) in layouts.iter_mut().zip(renamed) {
+        layout.name = name;
+        layout.fields = fields;
+    }
+    Ok(())
+}
+
+fn qualified_name(module_path: &[String], name: &str) -> String {
+    module_path
+        .iter()
+        .map(|module| module.to_camel_case())
+        .collect::<String>()
+        + name
+}
+
+/// Orders the layouts independently of the order they were collected in, so
+/// that the generated code is the same on every machine.
+///
+/// The layouts are sorted by crate, module path and name, then the layouts a
+/// layout refers to are moved before it. Layouts referring to each other are
+/// kept in the sorted order, which is safe, because the TypeScript classes
+/// only refer to each other in type annotations, and the schema entries are
+/// created after every class is declared.
+pub fn sort_layouts(layouts: &[Layout]) -> Vec<Layout> {
+    let mut sorted = layouts.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| {
+        (&a.crate_name, &a.module_path, &a.name, &a.source).cmp(&(
+            &b.crate_name,
+            &b.module_path,
+            &b.name,
+            &b.source,
+        ))
+    });
+    let mut indices = HashMap::new();
+    for (i, layout) in sorted.iter().enumerate() {
+        indices.entry(layout.name.as_str()).or_insert(i);
+    }
+    let mut visited = vec![false; sorted.len()];
+    let mut ordered = Vec::with_capacity(sorted.len());
+    for i in 0..sorted.len() {
+        visit_layout(i, &sorted, &indices, &mut visited, &mut ordered);
+    }
+    ordered
+}
+
+/// Appends the layout at `i` to `ordered` after the layouts it refers to.
+/// Layouts are marked as visited before their references, which breaks
+/// cycles.
+fn visit_layout(
+    i: usize,
+    layouts: &[&Layout],
+    indices: &HashMap<&str, usize>,
+    visited: &mut [bool],
+    ordered: &mut Vec<Layout>,
+) {
+    if visited[i] {
+        return;
+    }
+    visited[i] = true;
+    let mut referenced = Vec::new();
+    for field in &layouts[i].fields {
+        field.ty().referenced_layouts(&mut referenced);
+    }
+    for name in referenced {
+        if let Some(j) = indices.get(name.as_str()) {
+            visit_layout(*j, layouts, indices, visited, ordered);
+        }
+    }
+    ordered.push(layouts[i].clone());
+}
+
+/// Checks that every layout referenced by a field is one of `layouts`, i.e.
+/// that it is declared with `BorshSchemaTS` in the scanned sources, and lists
+/// every use of an undefined type otherwise.
+///
+/// Type aliases are already substituted when the layouts are generated, so
+/// the remaining references are either layouts or types the parser knows
+/// nothing about.
+pub fn validate(layouts: &[Layout]) -> Result<(), anyhow::Error> {
+    let defined = layouts
+        .iter()
+        .map(|layout| layout.name.as_str())
+        .collect::<HashSet<_>>();
+    let mut undefined = Vec::new();
+    for layout in layouts {
+        for field in &layout.fields {
+            let mut referenced = Vec::new();
+            field.ty().referenced_layouts(&mut referenced);
+            for name in referenced {
+                if defined.contains(name.as_str()) {
+                    continue;
+                }
+                let source = match &layout.source {
+                    Some(path) => format!("`{}`", path.display()),
+                    None => "unknown file".to_owned(),
+                };
+                undefined.push(format!(
+                    "  `{}` used by `{}.{}` in {}",
+                    name,
+                    display_path(&layout.module_path, &layout.name),
+                    field.name(),
+                    source
+                ));
+            }
+        }
+    }
+    if undefined.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "undefined types referenced by layouts:\n{}",
+            undefined.join("\n")
+        ))
+    }
+}
+
+/// Checks that the layouts that aren't generic don't refer to generic layouts,
+/// which is required for emitting generic layouts as generic classes. The
+/// schema of a generic layout is only known once its type arguments are, so
+/// the entries of the non-generic layouts would refer to classes without a
+/// schema.
+pub fn check_generic_uses(layouts: &[Layout]) -> Result<(), anyhow::Error> {
+    let generic = layouts
+        .iter()
+        .filter(|layout| layout.is_generic())
+        .map(|layout| layout.name.as_str())
+        .collect::<HashSet<_>>();
+    let mut uses = Vec::new();
+    for layout in layouts.iter().filter(|layout| !layout.is_generic()) {
+        for field in &layout.fields {
+            let mut instances = Vec::new();
+            field.ty().generic_instances(&mut instances);
+            for (name, _) in instances {
+                if generic.contains(name.as_str()) {
+                    uses.push(format!(
+                        "  `{}` used by `{}.{}`",
+                        name,
+                        display_path(&layout.module_path, &layout.name),
+                        field.name()
+                    ));
+                }
+            }
+        }
+    }
+    if uses.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "generic layouts used by other layouts have no schema without monomorphization:\n{}",
+            uses.join("\n")
+        ))
+    }
+}
+
+/// Returns the lines of the doc comments (`///` or `#[doc = "..."]`) in the
+/// attributes.
+pub fn doc_comments(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(doc),
+                ..
+            })) => Some(doc.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.split('\n')
+                .map(|line| line.strip_prefix(' ').unwrap_or(line).to_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn display_path(module_path: &[String], name: &str) -> String {
+    module_path
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(name))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+impl Layout {
+    /// Substitutes the generic type parameters of the layout and replaces
+    /// generic instantiations in its fields by monomorphized references. The
+    /// instantiations found are pushed into `instances` along with the crate
+    /// of the instantiated layout and their depth, which is one more than the
+    /// `depth` of the layout.
+    fn instantiate(
+        &self,
+        bindings: &HashMap<String, BorshType>,
+        crate_name: Option<String>,
+        depth: usize,
+        instances: &mut VecDeque<Instance>,
+    ) -> Layout {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                let ty = field.ty().substitute(bindings);
+                let mut found = Vec::new();
+                ty.generic_instances(&mut found);
+                instances.extend(
+                    found
+                        .into_iter()
+                        .map(|(name, args)| (name, args, crate_name.clone(), depth + 1)),
+                );
+                field.with_ty(ty.monomorphize())
+            })
+            .collect();
+        Layout {
+            name: self.name.clone(),
+            kind: self.kind.clone(),
+            fields,
+            generics: Vec::new(),
+            discriminants: self.discriminants.clone(),
+            module_path: self.module_path.clone(),
+            crate_name,
+            source: self.source.clone(),
+            location: self.location.clone(),
+            docs: self.docs.clone(),
+        }
     }
 }
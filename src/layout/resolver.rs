@@ -0,0 +1,670 @@
+use super::{qualified_name, BorshType, Definitions, ModuleScope, TypeAlias};
+use quote::ToTokens;
+
+use std::collections::HashMap;
+
+/// Aliases referring to each other deeper than this are considered to be
+/// recursive.
+const MAX_ALIAS_DEPTH: usize = 64;
+
+/// Constants referring to each other deeper than this are considered to be
+/// recursive.
+const MAX_CONST_DEPTH: usize = 64;
+
+/// Walks a [`syn::Type`] and resolves it into a [`BorshType`] using the
+/// collected [`Definitions`].
+pub struct TypeResolver<'a> {
+    definitions: &'a Definitions,
+    scope: Option<&'a ModuleScope>,
+    bindings: HashMap<String, BorshType>,
+    const_params: Vec<String>,
+    depth: usize,
+}
+
+impl<'a> TypeResolver<'a> {
+    pub fn new(definitions: &'a Definitions) -> Self {
+        Self {
+            definitions,
+            scope: None,
+            bindings: HashMap::new(),
+            const_params: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    /// Returns a resolver that resolves the given generic type parameters of a
+    /// layout into [`BorshType::TypeParam`]s.
+    pub fn with_type_params(&self, params: &[String]) -> Self {
+        let mut bindings = self.bindings.clone();
+        for param in params {
+            bindings.insert(param.clone(), BorshType::TypeParam(param.clone()));
+        }
+        Self {
+            definitions: self.definitions,
+            scope: self.scope,
+            bindings,
+            const_params: self.const_params.clone(),
+            depth: self.depth,
+        }
+    }
+
+    /// Returns a resolver that knows about the const generic parameters of a
+    /// layout, so that array lengths referring to them produce a meaningful
+    /// error.
+    pub fn with_const_params(&self, params: &[String]) -> Self {
+        let mut const_params = self.const_params.clone();
+        const_params.extend(params.iter().cloned());
+        Self {
+            definitions: self.definitions,
+            scope: self.scope,
+            bindings: self.bindings.clone(),
+            const_params,
+            depth: self.depth,
+        }
+    }
+
+    /// Returns a resolver that resolves `Self` into the layout of the `struct`
+    /// or `enum` named `ident`, which has the generic type parameters of
+    /// `generics`.
+    pub fn with_self(&self, ident: &syn::Ident, generics: &syn::Generics) -> Self {
+        let name = self
+            .layout_name(&syn::Path::from(ident.clone()))
+            .unwrap_or_else(|| ident.to_string());
+        let params = generics
+            .type_params()
+            .map(|param| BorshType::TypeParam(param.ident.to_string()))
+            .collect::<Vec<_>>();
+        let self_type = if params.is_empty() {
+            BorshType::Custom(name)
+        } else {
+            BorshType::Generic(name, params)
+        };
+        let mut bindings = self.bindings.clone();
+        bindings.insert("Self".to_owned(), self_type);
+        Self {
+            definitions: self.definitions,
+            scope: self.scope,
+            bindings,
+            const_params: self.const_params.clone(),
+            depth: self.depth,
+        }
+    }
+
+    /// Returns a resolver that resolves references to layouts declared in
+    /// other modules through the `use` declarations of `scope`.
+    pub fn with_scope<'b>(&'b self, scope: &'b ModuleScope) -> TypeResolver<'b> {
+        TypeResolver {
+            definitions: self.definitions,
+            scope: Some(scope),
+            bindings: self.bindings.clone(),
+            const_params: self.const_params.clone(),
+            depth: self.depth,
+        }
+    }
+
+    /// Resolves a type into its borsh representation.
+    pub fn resolve(&self, ty: &syn::Type) -> Result<BorshType, anyhow::Error> {
+        match ty {
+            syn::Type::Path(type_path) if type_path.qself.is_none() => {
+                self.resolve_path(&type_path.path)
+            }
+            syn::Type::Array(array) => {
+                let array_type = self.resolve(&array.elem)?;
+                let array_len = self.array_len(&array.len)?;
+                if let BorshType::U8 = array_type {
+                    Ok(BorshType::FixedBytes(array_len))
+                } else {
+                    Ok(BorshType::FixedArray(Box::new(array_type), array_len))
+                }
+            }
+            syn::Type::Slice(slice) => Ok(BorshType::Vec(Box::new(self.resolve(&slice.elem)?))),
+            syn::Type::Tuple(tuple) => Ok(BorshType::Tuple(
+                tuple
+                    .elems
+                    .iter()
+                    .map(|elem| self.resolve(elem))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            syn::Type::Reference(reference) => self.resolve(&reference.elem),
+            syn::Type::Paren(paren) => self.resolve(&paren.elem),
+            syn::Type::Group(group) => self.resolve(&group.elem),
+            syn::Type::Verbatim(tokens) => match syn::parse2::<syn::Type>(tokens.clone())? {
+                syn::Type::Verbatim(_) => Err(anyhow::anyhow!("invalid type `{}`", tokens)),
+                parsed => self.resolve(&parsed),
+            },
+            _ => Err(anyhow::anyhow!(
+                "unsupported type `{}`",
+                ty.to_token_stream()
+            )),
+        }
+    }
+
+    fn resolve_path(&self, path: &syn::Path) -> Result<BorshType, anyhow::Error> {
+        let segment = path
+            .segments
+            .last()
+            .ok_or_else(|| anyhow::anyhow!("empty type path"))?;
+        let ident = segment.ident.to_string();
+        if path.segments.len() == 1 {
+            if let Some(bound) = self.bindings.get(&ident) {
+                return Ok(bound.clone());
+            }
+        }
+        if let Some(alias) = self.alias(path, &ident)? {
+            return self.resolve_alias(&ident, alias, segment);
+        }
+        let borsh_type = match ident.as_ref() {
+            "u8" => BorshType::U8,
+            "u16" => BorshType::U16,
+            "u32" => BorshType::U32,
+            "u64" => BorshType::U64,
+            "u128" => BorshType::U128,
+            "i8" => BorshType::I8,
+            "i16" => BorshType::I16,
+            "i32" => BorshType::I32,
+            "i64" | "UnixTimestamp" => BorshType::I64,
+            "i128" => BorshType::I128,
+            "f32" => BorshType::F32,
+            "f64" => BorshType::F64,
+            "bool" => BorshType::Bool,
+            "String" | "str" => BorshType::String,
+            "Pubkey" => BorshType::Pubkey,
+            // smart pointers are serialized exactly as the type they wrap
+            "Box" | "Rc" | "Arc" | "Cow" => {
+                let [inner] = type_args::<1>(segment)?;
+                self.resolve(inner)?
+            }
+            // `PhantomData` is serialized as nothing
+            "PhantomData" => BorshType::Skip,
+            "Option" => {
+                let [inner] = type_args::<1>(segment)?;
+                BorshType::Option(Box::new(self.resolve(inner)?))
+            }
+            "Vec" | "VecDeque" => {
+                let [inner] = type_args::<1>(segment)?;
+                BorshType::Vec(Box::new(self.resolve(inner)?))
+            }
+            "BTreeMap" | "HashMap" => {
+                let [key, value] = type_args::<2>(segment)?;
+                BorshType::Map(Box::new(self.resolve(key)?), Box::new(self.resolve(value)?))
+            }
+            "BTreeSet" | "HashSet" => {
+                let [inner] = type_args::<1>(segment)?;
+                BorshType::Set(Box::new(self.resolve(inner)?))
+            }
+            _ => {
+                let args = generic_type_args(segment);
+                let name = self.layout_name(path).unwrap_or(ident);
+                if args.is_empty() {
+                    BorshType::Custom(name)
+                } else {
+                    BorshType::Generic(
+                        name,
+                        args.into_iter()
+                            .map(|arg| self.resolve(arg))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )
+                }
+            }
+        };
+        Ok(borsh_type)
+    }
+
+    /// Finds the layout a path refers to using the `use` declarations of the
+    /// module. Layouts declared in multiple modules are referred to by their
+    /// qualified name, e.g. `StateConfig` for `crate::state::Config`.
+    fn layout_name(&self, path: &syn::Path) -> Option<String> {
+        let segments = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+        self.scope?
+            .candidates(&segments)
+            .into_iter()
+            .find_map(|candidate| {
+                let (name, module) = candidate.split_last()?;
+                let modules = self.definitions.layout_modules(name);
+                if !modules.iter().any(|declared| declared == module) {
+                    None
+                } else if modules.len() > 1 {
+                    Some(qualified_name(module, name))
+                } else {
+                    Some(name.clone())
+                }
+            })
+    }
+
+    /// Finds the type alias a path refers to. The modules the path may refer
+    /// to are searched for aliases and layouts in order of precedence, so an
+    /// alias doesn't shadow a layout with the same name declared in another
+    /// module. Otherwise aliases declared in any module are used, unless a
+    /// layout with that name exists.
+    fn alias(&self, path: &syn::Path, ident: &str) -> Result<Option<&'a TypeAlias>, anyhow::Error> {
+        if let Some(scope) = self.scope {
+            let segments = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>();
+            for candidate in scope.candidates(&segments) {
+                let (name, module) = match candidate.split_last() {
+                    Some(split) => split,
+                    None => continue,
+                };
+                if let Some(alias) = self.definitions.module_alias(name, module)? {
+                    return Ok(Some(alias));
+                }
+                let modules = self.definitions.layout_modules(name);
+                if modules.iter().any(|declared| declared == module) {
+                    return Ok(None);
+                }
+            }
+        }
+        if !self.definitions.layout_modules(ident).is_empty() {
+            return Ok(None);
+        }
+        self.definitions.alias(ident)
+    }
+
+    /// Substitutes a type alias by the type it stands for. Generic parameters
+    /// of the alias are bound to the (resolved) arguments of the use site.
+    fn resolve_alias(
+        &self,
+        name: &str,
+        alias: &TypeAlias,
+        segment: &syn::PathSegment,
+    ) -> Result<BorshType, anyhow::Error> {
+        if self.depth >= MAX_ALIAS_DEPTH {
+            return Err(anyhow::anyhow!("type alias `{}` is recursive", name));
+        }
+        let args = generic_type_args(segment);
+        if args.len() > alias.params.len() {
+            return Err(anyhow::anyhow!(
+                "type alias `{}` expects {} type argument(s), found {}",
+                name,
+                alias.params.len(),
+                args.len()
+            ));
+        }
+        let mut resolver = TypeResolver {
+            definitions: self.definitions,
+            scope: self.scope,
+            bindings: HashMap::new(),
+            const_params: Vec::new(),
+            depth: self.depth + 1,
+        };
+        for (i, (param, default)) in alias.params.iter().enumerate() {
+            let bound = match (args.get(i), default) {
+                (Some(arg), _) => self.resolve(arg)?,
+                // defaults may refer to previous parameters of the alias
+                (None, Some(default)) => resolver.resolve(default)?,
+                (None, None) => {
+                    return Err(anyhow::anyhow!(
+                        "missing type argument `{}` of type alias `{}`",
+                        param,
+                        name
+                    ))
+                }
+            };
+            resolver.bindings.insert(param.clone(), bound);
+        }
+        resolver.resolve(&alias.ty)
+    }
+
+    /// Evaluates an explicit discriminant of an `enum` variant, which has to
+    /// fit into the `u8` used by borsh.
+    pub fn eval_discriminant(&self, expr: &syn::Expr) -> Result<u8, anyhow::Error> {
+        if let syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            ..
+        }) = expr
+        {
+            return Err(anyhow::anyhow!(
+                "negative discriminant `{}` can't be serialized by borsh",
+                expr.to_token_stream()
+            ));
+        }
+        let discriminant = self.eval_const(expr, 0)?;
+        u8::try_from(discriminant).map_err(|_| {
+            anyhow::anyhow!(
+                "discriminant `{}` doesn't fit into a `u8`",
+                expr.to_token_stream()
+            )
+        })
+    }
+
+    /// Evaluates the length of a fixed size array.
+    fn array_len(&self, expr: &syn::Expr) -> Result<usize, anyhow::Error> {
+        let len = self.eval_const(expr, 0)?;
+        usize::try_from(len)
+            .map_err(|_| anyhow::anyhow!("array length `{}` is too large", expr.to_token_stream()))
+    }
+
+    /// Evaluates a constant expression consisting of integer literals,
+    /// arithmetic, casts, named constants and `size_of` of primitive types.
+    fn eval_const(&self, expr: &syn::Expr, depth: usize) -> Result<u128, anyhow::Error> {
+        match expr {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(int),
+                ..
+            }) => Ok(int.base10_parse::<u128>()?),
+            syn::Expr::Paren(paren) => self.eval_const(&paren.expr, depth),
+            syn::Expr::Group(group) => self.eval_const(&group.expr, depth),
+            syn::Expr::Cast(cast) => {
+                let value = self.eval_const(&cast.expr, depth)?;
+                let (bits, signed) = int_type(&cast.ty).ok_or_else(|| unsupported_const(expr))?;
+                // `as` keeps the low bits of the value
+                let value = if bits < u128::BITS {
+                    value & ((1 << bits) - 1)
+                } else {
+                    value
+                };
+                if signed && value >> (bits - 1) != 0 {
+                    return Err(anyhow::anyhow!(
+                        "failed to evaluate `{}`: the value is negative",
+                        expr.to_token_stream()
+                    ));
+                }
+                Ok(value)
+            }
+            syn::Expr::Binary(binary) => {
+                let left = self.eval_const(&binary.left, depth)?;
+                let right = self.eval_const(&binary.right, depth)?;
+                let value = match binary.op {
+                    syn::BinOp::Add(_) => left.checked_add(right),
+                    syn::BinOp::Sub(_) => left.checked_sub(right),
+                    syn::BinOp::Mul(_) => left.checked_mul(right),
+                    syn::BinOp::Div(_) => left.checked_div(right),
+                    syn::BinOp::Rem(_) => left.checked_rem(right),
+                    syn::BinOp::Shl(_) => u32::try_from(right)
+                        .ok()
+                        .and_then(|right| left.checked_shl(right)),
+                    syn::BinOp::Shr(_) => u32::try_from(right)
+                        .ok()
+                        .and_then(|right| left.checked_shr(right)),
+                    syn::BinOp::BitAnd(_) => Some(left & right),
+                    syn::BinOp::BitOr(_) => Some(left | right),
+                    syn::BinOp::BitXor(_) => Some(left ^ right),
+                    _ => return Err(unsupported_const(expr)),
+                };
+                value.ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "failed to evaluate `{}`: arithmetic overflow",
+                        expr.to_token_stream()
+                    )
+                })
+            }
+            syn::Expr::Path(expr_path) if expr_path.qself.is_none() => {
+                let ident = expr_path
+                    .path
+                    .segments
+                    .last()
+                    .ok_or_else(|| unsupported_const(expr))?
+                    .ident
+                    .to_string();
+                if self.const_params.contains(&ident) {
+                    return Err(anyhow::anyhow!(
+                        "array length refers to the const generic parameter `{}`, \
+                        use the `alias` attribute to specify the type",
+                        ident
+                    ));
+                }
+                if depth >= MAX_CONST_DEPTH {
+                    return Err(anyhow::anyhow!("constant `{}` is recursive", ident));
+                }
+                match self.definitions.constant(&ident)? {
+                    Some(value) => self
+                        .eval_const(value, depth + 1)
+                        .map_err(|err| err.context(format!("in constant `{}`", ident))),
+                    None => Err(anyhow::anyhow!(
+                        "unresolved constant `{}` in array length",
+                        ident
+                    )),
+                }
+            }
+            syn::Expr::Call(call) if call.args.is_empty() => match call.func.as_ref() {
+                syn::Expr::Path(func) => {
+                    let segment = func
+                        .path
+                        .segments
+                        .last()
+                        .ok_or_else(|| unsupported_const(expr))?;
+                    if segment.ident != "size_of" {
+                        return Err(unsupported_const(expr));
+                    }
+                    let [ty] = type_args::<1>(segment)?;
+                    self.size_of(ty)
+                }
+                _ => Err(unsupported_const(expr)),
+            },
+            _ => Err(unsupported_const(expr)),
+        }
+    }
+
+    /// Returns the size of a primitive type in bytes.
+    fn size_of(&self, ty: &syn::Type) -> Result<u128, anyhow::Error> {
+        let size = match self.resolve(ty)? {
+            BorshType::U8 | BorshType::I8 | BorshType::Bool => 1,
+            BorshType::U16 | BorshType::I16 => 2,
+            BorshType::U32 | BorshType::I32 | BorshType::F32 => 4,
+            BorshType::U64 | BorshType::I64 | BorshType::F64 => 8,
+            BorshType::U128 | BorshType::I128 => 16,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "`size_of` is only supported for primitive types, found `{}`",
+                    ty.to_token_stream()
+                ))
+            }
+        };
+        Ok(size)
+    }
+}
+
+/// Returns the number of bits of an integer type and whether it is signed.
+/// `usize` and `isize` have 64 bits, as on the targets of Solana programs.
+fn int_type(ty: &syn::Type) -> Option<(u32, bool)> {
+    let ident = match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() => {
+            type_path.path.get_ident()?.to_string()
+        }
+        _ => return None,
+    };
+    let int_type = match ident.as_str() {
+        "u8" => (8, false),
+        "u16" => (16, false),
+        "u32" => (32, false),
+        "u64" | "usize" => (64, false),
+        "u128" => (128, false),
+        "i8" => (8, true),
+        "i16" => (16, true),
+        "i32" => (32, true),
+        "i64" | "isize" => (64, true),
+        "i128" => (128, true),
+        _ => return None,
+    };
+    Some(int_type)
+}
+
+fn unsupported_const(expr: &syn::Expr) -> anyhow::Error {
+    anyhow::anyhow!("unsupported array length `{}`", expr.to_token_stream())
+}
+
+/// Returns the generic type arguments of a path segment, lifetimes are
+/// ignored.
+fn generic_type_args(segment: &syn::PathSegment) -> Vec<&syn::Type> {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the first `N` generic type arguments of a path segment, e.g. the
+/// key and value types of `HashMap<K, V, S>`.
+fn type_args<const N: usize>(segment: &syn::PathSegment) -> Result<[&syn::Type; N], anyhow::Error> {
+    let mut args = generic_type_args(segment);
+    args.truncate(N);
+    args.try_into()
+        .map_err(|_| anyhow::anyhow!("invalid {}, expected {} type argument(s)", segment.ident, N))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn definitions(code: &str) -> Definitions {
+        let mut definitions = Definitions::default();
+        definitions.collect(&syn::parse_file(code).unwrap().items, &[]);
+        definitions
+    }
+
+    fn resolve(definitions: &Definitions, ty: &str) -> Result<BorshType, anyhow::Error> {
+        TypeResolver::new(definitions).resolve(&syn::parse_str(ty).unwrap())
+    }
+
+    #[test]
+    fn alias_resolution() {
+        let definitions = definitions(
+            "type Hash = [u8; 32];
+            type HashAlias = Hash;
+            pub type Hashes = Vec<HashAlias>;
+            type Pair<T> = (T, T);
+            type Lookup<K, V = Hash> = BTreeMap<K, Option<V>>;",
+        );
+        assert_eq!(
+            resolve(&definitions, "Hash").unwrap(),
+            BorshType::FixedBytes(32)
+        );
+        assert_eq!(
+            resolve(&definitions, "Option<HashAlias>").unwrap(),
+            BorshType::Option(Box::new(BorshType::FixedBytes(32)))
+        );
+        assert_eq!(
+            resolve(&definitions, "Hashes").unwrap(),
+            BorshType::Vec(Box::new(BorshType::FixedBytes(32)))
+        );
+        assert_eq!(
+            resolve(&definitions, "Pair<u16>").unwrap(),
+            BorshType::Tuple(vec![BorshType::U16, BorshType::U16])
+        );
+        assert_eq!(
+            resolve(&definitions, "Pair<Pair<Hash>>").unwrap(),
+            BorshType::Tuple(vec![
+                BorshType::Tuple(vec![BorshType::FixedBytes(32), BorshType::FixedBytes(32)]),
+                BorshType::Tuple(vec![BorshType::FixedBytes(32), BorshType::FixedBytes(32)]),
+            ])
+        );
+        assert_eq!(
+            resolve(&definitions, "Lookup<String>").unwrap(),
+            BorshType::Map(
+                Box::new(BorshType::String),
+                Box::new(BorshType::Option(Box::new(BorshType::FixedBytes(32))))
+            )
+        );
+        assert_eq!(
+            resolve(&definitions, "Lookup<u8, T>").unwrap(),
+            BorshType::Map(
+                Box::new(BorshType::U8),
+                Box::new(BorshType::Option(Box::new(BorshType::Custom(
+                    "T".to_owned()
+                ))))
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_alias_resolution() {
+        let definitions = definitions(
+            "type Loop = Vec<Loop>;
+            type Pair<T> = (T, T);
+            type Ambiguous = u8;",
+        );
+        assert!(resolve(&definitions, "Loop").is_err());
+        assert!(resolve(&definitions, "Pair").is_err());
+        assert!(resolve(&definitions, "Pair<u8, u8, u8>").is_err());
+        assert_eq!(resolve(&definitions, "Ambiguous").unwrap(), BorshType::U8);
+
+        let mut definitions = definitions;
+        definitions.collect(
+            &syn::parse_file("type Ambiguous = u16;").unwrap().items,
+            &[],
+        );
+        assert!(resolve(&definitions, "Ambiguous").is_err());
+        definitions.collect(
+            &syn::parse_file("type Pair<T> = (T, T);").unwrap().items,
+            &[],
+        );
+        assert!(resolve(&definitions, "Pair<u8>").is_ok());
+    }
+
+    #[test]
+    fn const_array_length_resolution() {
+        let definitions = definitions(
+            "const MAX_NAME_LEN: usize = 32;
+            pub const N_SIGNERS: usize = MAX_NAME_LEN / 8 - 1;
+            const DOUBLE: usize = (32 * 2) as usize;
+            const WORDS: usize = 3 * std::mem::size_of::<u64>() + size_of::<bool>();",
+        );
+        assert_eq!(
+            resolve(&definitions, "[u8; MAX_NAME_LEN]").unwrap(),
+            BorshType::FixedBytes(32)
+        );
+        assert_eq!(
+            resolve(&definitions, "[Pubkey; N_SIGNERS]").unwrap(),
+            BorshType::FixedArray(Box::new(BorshType::Pubkey), 3)
+        );
+        assert_eq!(
+            resolve(&definitions, "[u8; DOUBLE + 1]").unwrap(),
+            BorshType::FixedBytes(65)
+        );
+        assert_eq!(
+            resolve(&definitions, "[u16; crate::consts::WORDS]").unwrap(),
+            BorshType::FixedArray(Box::new(BorshType::U16), 25)
+        );
+        // casts truncate the value like `as` does
+        assert_eq!(
+            resolve(&definitions, "[u8; 300 as u8]").unwrap(),
+            BorshType::FixedBytes(44)
+        );
+        assert_eq!(
+            resolve(&definitions, "[u8; (65537 as u16) as usize]").unwrap(),
+            BorshType::FixedBytes(1)
+        );
+        assert_eq!(
+            resolve(&definitions, "[u8; 300 as i8]").unwrap(),
+            BorshType::FixedBytes(44)
+        );
+    }
+
+    #[test]
+    fn invalid_const_array_length_resolution() {
+        let definitions = definitions(
+            "const LOOP: usize = LOOP + 1;
+            const MISSING: usize = UNKNOWN * 2;
+            const UNDERFLOW: usize = 1 - 2;",
+        );
+        let err = resolve(&definitions, "[u8; UNKNOWN]").unwrap_err();
+        assert!(err.to_string().contains("`UNKNOWN`"));
+        let err = resolve(&definitions, "[u8; MISSING]").unwrap_err();
+        assert!(format!("{:#}", err).contains("`UNKNOWN`"));
+        assert!(resolve(&definitions, "[u8; LOOP]").is_err());
+        assert!(resolve(&definitions, "[u8; UNDERFLOW]").is_err());
+        assert!(resolve(&definitions, "[u8; 200 as i8]").is_err());
+        assert!(resolve(&definitions, "[u8; 3 as f32]").is_err());
+        assert!(resolve(&definitions, "[u8; size_of::<String>()]").is_err());
+        assert!(resolve(&definitions, "[u8; len()]").is_err());
+
+        let resolver = TypeResolver::new(&definitions).with_const_params(&["N".to_owned()]);
+        let err = resolver
+            .resolve(&syn::parse_str("[u8; N]").unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("const generic parameter `N`"));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+/// The crate a scanned module belongs to when layouts are collected from
+/// multiple crates.
+#[derive(Clone, Debug, Default)]
+pub struct CrateScope {
+    /// Name of the crate, used as the first segment of the module paths of
+    /// the crate.
+    pub name: Option<String>,
+    /// The names of the dependencies as used in the crate's source, mapped to
+    /// the names of the collected crates.
+    pub externs: HashMap<String, String>,
+}
+
+/// The names imported into a module by `use` declarations, used for finding
+/// the module a type refers to.
+#[derive(Clone, Debug, Default)]
+pub struct ModuleScope {
+    path: Vec<String>,
+    crate_scope: CrateScope,
+    imports: HashMap<String, Vec<String>>,
+    globs: Vec<Vec<String>>,
+}
+
+impl ModuleScope {
+    /// Collects the `use` declarations of a module.
+    pub fn new(path: &[String], items: &[syn::Item], crate_scope: &CrateScope) -> Self {
+        let mut scope = Self {
+            path: path.to_vec(),
+            crate_scope: crate_scope.clone(),
+            ..Self::default()
+        };
+        for item in items {
+            if let syn::Item::Use(item_use) = item {
+                scope.collect_use(&item_use.tree, Vec::new());
+            }
+        }
+        scope
+    }
+
+    fn collect_use(&mut self, tree: &syn::UseTree, mut prefix: Vec<String>) {
+        match tree {
+            syn::UseTree::Path(use_path) => {
+                prefix.push(use_path.ident.to_string());
+                self.collect_use(&use_path.tree, prefix);
+            }
+            syn::UseTree::Name(use_name) => {
+                let name = if use_name.ident == "self" {
+                    match prefix.last() {
+                        Some(name) => name.clone(),
+                        None => return,
+                    }
+                } else {
+                    prefix.push(use_name.ident.to_string());
+                    use_name.ident.to_string()
+                };
+                let path = self.absolute_path(&prefix);
+                self.imports.insert(name, path);
+            }
+            syn::UseTree::Rename(use_rename) => {
+                if use_rename.ident != "self" {
+                    prefix.push(use_rename.ident.to_string());
+                }
+                let path = self.absolute_path(&prefix);
+                self.imports.insert(use_rename.rename.to_string(), path);
+            }
+            syn::UseTree::Glob(_) => {
+                let path = self.absolute_path(&prefix);
+                self.globs.push(path);
+            }
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.collect_use(tree, prefix.clone());
+                }
+            }
+        }
+    }
+
+    /// Converts a path starting with `crate`, `self`, `super` or the name of
+    /// a collected dependency into a path relative to the root of the scanned
+    /// sources. Other paths are considered to be relative to the module.
+    fn absolute_path(&self, segments: &[String]) -> Vec<String> {
+        let mut path = self.path.clone();
+        let mut segments = segments.iter().peekable();
+        match segments.peek().map(|segment| segment.as_str()) {
+            Some("crate") => {
+                path = self.crate_scope.name.iter().cloned().collect();
+                segments.next();
+            }
+            Some(name) if self.crate_scope.externs.contains_key(name) => {
+                path = vec![self.crate_scope.externs[name].clone()];
+                segments.next();
+            }
+            Some("self") => {
+                segments.next();
+            }
+            _ => {}
+        }
+        while segments.peek().map(|segment| segment.as_str()) == Some("super") {
+            path.pop();
+            segments.next();
+        }
+        path.extend(segments.cloned());
+        path
+    }
+
+    /// Returns the paths, relative to the crate root, that a type path used in
+    /// the module may refer to, in order of precedence.
+    pub fn candidates(&self, segments: &[String]) -> Vec<Vec<String>> {
+        let (first, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => return Vec::new(),
+        };
+        if matches!(first.as_str(), "crate" | "self" | "super")
+            || self.crate_scope.externs.contains_key(first)
+        {
+            return vec![self.absolute_path(segments)];
+        }
+        let mut candidates = Vec::new();
+        if let Some(import) = self.imports.get(first) {
+            let mut candidate = import.clone();
+            candidate.extend(rest.iter().cloned());
+            candidates.push(candidate);
+        }
+        candidates.push(self.absolute_path(segments));
+        for glob in &self.globs {
+            let mut candidate = glob.clone();
+            candidate.extend(segments.iter().cloned());
+            candidates.push(candidate);
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split("::").map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn use_resolution() {
+        let file = syn::parse_file(
+            "use crate::state::{self, Config as StateConfig};
+            use super::instruction::*;
+            use self::args::Args;
+            use borsh::BorshSerialize;",
+        )
+        .unwrap();
+        let scope = ModuleScope::new(
+            &path("processor::inner"),
+            &file.items,
+            &CrateScope::default(),
+        );
+
+        assert_eq!(
+            scope.candidates(&path("StateConfig")),
+            vec![
+                path("state::Config"),
+                path("processor::inner::StateConfig"),
+                path("processor::instruction::StateConfig"),
+            ]
+        );
+        assert_eq!(
+            scope.candidates(&path("state::Account")),
+            vec![
+                path("state::Account"),
+                path("processor::inner::state::Account"),
+                path("processor::instruction::state::Account"),
+            ]
+        );
+        assert_eq!(
+            scope.candidates(&path("Args"))[0],
+            path("processor::inner::args::Args")
+        );
+        assert_eq!(
+            scope.candidates(&path("super::super::Root")),
+            vec![path("Root")]
+        );
+        assert_eq!(scope.candidates(&path("crate::a::B")), vec![path("a::B")]);
+        assert_eq!(
+            scope.candidates(&path("BorshSerialize"))[0],
+            path("processor::inner::borsh::BorshSerialize")
+        );
+    }
+
+    #[test]
+    fn extern_crate_resolution() {
+        let file = syn::parse_file(
+            "use common::state::Config;
+            use crate::state::Account;",
+        )
+        .unwrap();
+        let mut crate_scope = CrateScope {
+            name: Some("program".to_owned()),
+            externs: HashMap::new(),
+        };
+        crate_scope
+            .externs
+            .insert("common".to_owned(), "my_common".to_owned());
+        let scope = ModuleScope::new(&path("program::processor"), &file.items, &crate_scope);
+
+        assert_eq!(
+            scope.candidates(&path("Config"))[0],
+            path("my_common::state::Config")
+        );
+        assert_eq!(
+            scope.candidates(&path("Account"))[0],
+            path("program::state::Account")
+        );
+        assert_eq!(
+            scope.candidates(&path("common::Shared")),
+            vec![path("my_common::Shared")]
+        );
+        assert_eq!(
+            scope.candidates(&path("crate::Root")),
+            vec![path("program::Root")]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+/// Naming convention of the fields in the generated classes and schemas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldNaming {
+    /// `some_field` becomes `someField`.
+    #[default]
+    CamelCase,
+    /// `someField` becomes `some_field`.
+    SnakeCase,
+    /// Fields are named as in the Rust source.
+    Preserve,
+}
+
+/// Determines how the fields of the generated classes are declared.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Nullability {
+    /// `field: T | undefined;`
+    #[default]
+    Undefined,
+    /// `field?: T;`
+    Optional,
+    /// `field!: T;`
+    Definite,
+}
+
+impl Nullability {
+    /// Returns the declaration of a class field (without the `;`).
+    pub fn declare(self, name: &str, ty: &str) -> String {
+        match self {
+            Self::Undefined => format!("{}: {} | undefined", name, ty),
+            Self::Optional => format!("{}?: {}", name, ty),
+            Self::Definite => format!("{}!: {}", name, ty),
+        }
+    }
+}
+
+/// Determines how public keys are represented in the generated classes and
+/// schemas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PubkeyStrategy {
+    /// The `PublicKeyBE` class of `ts-borsh-schema`, which is added to the
+    /// schema.
+    #[default]
+    PublicKeyBE,
+    /// The `PublicKey` class of `@solana/web3.js`. The generated schema
+    /// registers a `pubkey` field type with `borsh`.
+    Web3,
+    /// The 32 bytes of the key as a `Uint8Array`.
+    Bytes,
+    /// The key as a base58 string, encoded with `bs58`. The generated schema
+    /// registers a `base58Pubkey` field type with `borsh`.
+    Base58,
+}
+
+impl PubkeyStrategy {
+    /// Returns the type of public keys in the classes.
+    pub fn class_type(self) -> &'static str {
+        match self {
+            Self::PublicKeyBE => "PublicKeyBE",
+            Self::Web3 => "PublicKey",
+            Self::Bytes => "Uint8Array",
+            Self::Base58 => "string",
+        }
+    }
+
+    /// Returns the type of public keys in the schemas.
+    pub fn schema_type(self) -> &'static str {
+        match self {
+            Self::PublicKeyBE => "PublicKeyBE",
+            Self::Web3 => "'pubkey'",
+            Self::Bytes => "[32]",
+            Self::Base58 => "'base58Pubkey'",
+        }
+    }
+}
+
+/// Code style of the generated classes and schemas. The default style is the
+/// one `ts-borsh-schema` expects.
+#[derive(Clone, Debug)]
+pub struct CodeStyle {
+    pub field_naming: FieldNaming,
+    pub nullability: Nullability,
+    pub pubkey: PubkeyStrategy,
+    /// A single level of indentation.
+    pub indent: String,
+}
+
+impl Default for CodeStyle {
+    fn default() -> Self {
+        Self {
+            field_naming: FieldNaming::default(),
+            nullability: Nullability::default(),
+            pubkey: PubkeyStrategy::default(),
+            indent: "  ".to_owned(),
+        }
+    }
+}
+
+impl CodeStyle {
+    /// Returns `level` levels of indentation.
+    pub fn indent(&self, level: usize) -> String {
+        self.indent.repeat(level)
+    }
+
+    /// Replaces the two-space indentation of `code` by the indentation of
+    /// the style.
+    pub fn reindent(&self, code: &str) -> String {
+        code.split('\n')
+            .map(|line| {
+                let content = line.trim_start_matches(' ');
+                self.indent((line.len() - content.len()) / 2) + content
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn field_declaration() {
+        assert_eq!(
+            Nullability::Undefined.declare("a", "BN"),
+            "a: BN | undefined"
+        );
+        assert_eq!(Nullability::Optional.declare("a", "BN"), "a?: BN");
+        assert_eq!(Nullability::Definite.declare("a", "BN"), "a!: BN");
+    }
+
+    #[test]
+    fn reindentation() {
+        let code = "if (a) {\n  b();\n    c();\n}\n";
+        assert_eq!(CodeStyle::default().reindent(code), code);
+        let style = CodeStyle {
+            indent: "\t".to_owned(),
+            ..CodeStyle::default()
+        };
+        assert_eq!(style.reindent(code), "if (a) {\n\tb();\n\t\tc();\n}\n");
+    }
+}
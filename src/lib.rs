@@ -20,6 +20,12 @@
 //! The parser itself is only available through the `full` feature flag,
 //! because it uses parsing libraries incompatible with `wasm` or `bpf`
 //! targets.
+//!
+//! The intermediate data structures ([`Layout`], [`LayoutField`] and
+//! [`BorshType`]) are public, so the generated layouts can be inspected,
+//! transformed or passed to custom generators. With the `serde` feature flag
+//! they implement `Serialize` and `Deserialize`, e.g. for caching them as
+//! JSON.
 
 pub use agsol_borsh_schema_derive::*;
 
@@ -31,16 +37,25 @@ mod layout;
 mod test;
 #[cfg(feature = "full")]
 mod utils;
+#[cfg(feature = "full")]
+mod workspace;
 
+#[cfg(feature = "full")]
+pub use layout::{
+    BorshType, CfgSet, CodeStyle, Diagnostic, Diagnostics, EmitError, FieldNaming, Kind, Layout,
+    LayoutField, Location, Nullability, PubkeyStrategy,
+};
 #[cfg(feature = "full")]
 pub use utils::*;
 
 /// An empty trait that serves as a flag for the schema parser.
 ///
 /// It has an `alias` attribute that can be used to annotate `struct` and
-/// `enum` fields to explicitly indicate the type of that field. This is needed
-/// because the parser reads the file as a raw string, therefore it has no way
-/// of knowing the underlying type of a type alias.
+/// `enum` fields to explicitly indicate the type of that field. Type aliases
+/// declared in the scanned files (e.g. `type SomeAlias = [u8; 32];`) are
+/// resolved automatically, the attribute is only needed for overriding the
+/// type of a field, or when the alias is declared outside of the scanned
+/// files.
 ///
 /// # Example
 /// ```rust
@@ -48,13 +63,15 @@ pub use utils::*;
 /// use std::collections::BTreeMap;
 ///
 /// type SomeAlias = [u8; 32];
+/// type ExternalAlias = u64;
 ///
 /// #[derive(BorshSchemaTS)]
 /// struct Foo {
 ///     foo: Option<u64>,
 ///     bar: BTreeMap<u8, Bar>,
-///     #[alias([u8; 32])]
 ///     baz: SomeAlias,
+///     #[alias(u64)]
+///     quux: ExternalAlias,
 /// }
 ///
 /// #[derive(BorshSchemaTS)]
@@ -70,9 +87,12 @@ pub use utils::*;
 /// ```
 ///
 /// In the above example you may notice that `Foo`'s `bar` field doesn't need
-/// an alias because `Bar` implements `BorshSchemaTS` itself, however, the parser
-/// doesn't know that `SomeAlias` is actually a byte array without the `alias`
-/// attribute. If the `alias` attribute is omitted, the generated TypeScript
-/// code will contain `SomeAlias` instead of `Uint8Array`.
+/// an alias because `Bar` implements `BorshSchemaTS` itself, and `baz` doesn't
+/// need one either, because the parser substitutes `SomeAlias` by the byte
+/// array it stands for. Suppose that `ExternalAlias` is declared in a
+/// different crate: the parser doesn't know that it is actually a `u64`
+/// without the `alias` attribute, and the generated TypeScript code would
+/// contain `ExternalAlias` instead of `BN`.
 pub trait BorshSchemaTS {}
-static ATTRIBUTE_LABEL : &str = "BorshSchemaTS";
+#[cfg(feature = "full")]
+static ATTRIBUTE_LABEL: &str = "BorshSchemaTS";
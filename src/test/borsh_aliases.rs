@@ -0,0 +1,113 @@
+use super::borsh_structs::Amount;
+use super::decode::{Decoder, Value};
+use super::TEST_DATA_DIRECTORY;
+use crate::layout::{lift_tuples, BorshType};
+use crate::{generate_layout_from_file, generate_layouts, BorshSchemaTS};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestData {
+    alias_struct: Vec<u8>,
+}
+
+type Hash = [u8; 32];
+type Digest = Hash;
+type Pair<T> = (T, T);
+pub type Registry<K, V = Digest> = BTreeMap<K, Vec<V>>;
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct AliasStruct {
+    hash: Hash,
+    digest: Option<Digest>,
+    bounds: Pair<i16>,
+    registry: Registry<String>,
+    #[alias(u32)]
+    overridden: Pair<u16>,
+    amount: Amount,
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_aliases.rs").unwrap();
+    assert_eq!(layouts.len(), 1);
+    assert_eq!(layouts[0].name, "AliasStruct");
+    let types = layouts[0]
+        .fields
+        .iter()
+        .map(|field| field.ty().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            BorshType::FixedBytes(32),
+            BorshType::Option(Box::new(BorshType::FixedBytes(32))),
+            BorshType::Tuple(vec![BorshType::I16, BorshType::I16]),
+            BorshType::Map(
+                Box::new(BorshType::String),
+                Box::new(BorshType::Vec(Box::new(BorshType::FixedBytes(32))))
+            ),
+            BorshType::U32,
+            // declared in another file
+            BorshType::Custom("Amount".to_owned()),
+        ]
+    );
+
+    // aliases declared anywhere in the scanned directory are resolved
+    let layouts = generate_layouts("src/test").unwrap();
+    let alias_layout = layouts
+        .iter()
+        .find(|layout| layout.name == "AliasStruct")
+        .unwrap();
+    assert_eq!(alias_layout.fields[5].ty(), &BorshType::U64);
+
+    let mut registry = BTreeMap::new();
+    registry.insert("key".to_owned(), vec![[7; 32]]);
+    let alias_struct = AliasStruct {
+        hash: [1; 32],
+        digest: None,
+        bounds: (-1, 1),
+        registry,
+        overridden: (2, 0),
+        amount: 1_000,
+    };
+    let data = alias_struct.try_to_vec().unwrap();
+    let lifted = lift_tuples(&layouts).unwrap();
+    let decoded = Decoder::new(&lifted, &data).decode_layout("AliasStruct");
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            ("hash".to_owned(), Value::Bytes(vec![1; 32])),
+            ("digest".to_owned(), Value::Option(None)),
+            (
+                "bounds".to_owned(),
+                Value::Struct(vec![
+                    ("unnamed_0".to_owned(), Value::Signed(-1)),
+                    ("unnamed_1".to_owned(), Value::Signed(1)),
+                ])
+            ),
+            (
+                "registry".to_owned(),
+                Value::Map(vec![(
+                    Value::String("key".to_owned()),
+                    Value::Seq(vec![Value::Bytes(vec![7; 32])])
+                )])
+            ),
+            ("overridden".to_owned(), Value::Unsigned(2)),
+            ("amount".to_owned(), Value::Unsigned(1_000)),
+        ])
+    );
+
+    let test_data = TestData { alias_struct: data };
+
+    fs::create_dir_all(TEST_DATA_DIRECTORY).unwrap();
+    let mut file =
+        fs::File::create(String::from(TEST_DATA_DIRECTORY) + "/test_aliases.json").unwrap();
+    write!(file, "{}", serde_json::to_string(&test_data).unwrap()).unwrap();
+}
@@ -0,0 +1,44 @@
+use super::decode::{Decoder, Value};
+use crate::{generate_layout_from_file, BorshSchemaTS};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use std::collections::HashMap;
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CachedAccount {
+    owner_id: u32,
+    #[borsh_skip]
+    cache: HashMap<u32, String>,
+    balance: u64,
+    #[borsh_skip]
+    dirty: bool,
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_attributes.rs").unwrap();
+    assert_eq!(layouts.len(), 1);
+    assert!(layouts[0].fields[1].should_skip());
+    assert!(layouts[0].fields[3].should_skip());
+    assert!(!layouts[0].to_ts_class().unwrap().contains("cache"));
+    assert!(!layouts[0].to_borsh_schema().unwrap().contains("dirty"));
+
+    let mut cache = HashMap::new();
+    cache.insert(1, "cached".to_owned());
+    let account = CachedAccount {
+        owner_id: 5,
+        cache,
+        balance: 1_000,
+        dirty: true,
+    };
+    let data = account.try_to_vec().unwrap();
+    let decoded = Decoder::new(&layouts, &data).decode_layout("CachedAccount");
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            ("ownerId".to_owned(), Value::Unsigned(5)),
+            ("balance".to_owned(), Value::Unsigned(1_000)),
+        ])
+    );
+}
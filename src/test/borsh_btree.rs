@@ -1,10 +1,12 @@
+use super::decode::{Decoder, Value};
 use super::TEST_DATA_DIRECTORY;
+use crate::layout::BorshType;
 use crate::{BorshSchemaTS, generate_layout_from_file};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::borsh::try_from_slice_unchecked;
 use solana_program::pubkey::Pubkey;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::io::Write;
 
@@ -16,11 +18,23 @@ pub struct BTreeWrapper {
     map2: BTreeMap<u16, String>,
 }
 
+/// `borsh-rs` sorts the entries of hashed collections by key before
+/// serializing them, therefore `HashMap` and `HashSet` end up with the same
+/// byte representation as their `BTreeMap` and `BTreeSet` counterparts, and
+/// they share the same schema.
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize)]
+pub struct HashWrapper {
+    map: HashMap<u16, String>,
+    set: HashSet<[u8; 4]>,
+    ordered_set: BTreeSet<String>,
+}
+
 #[test]
 fn generate_layout_from_this_file() {
     let layouts = generate_layout_from_file("src/test/borsh_btree.rs").unwrap();
-    assert_eq!(layouts.len(), 1);
+    assert_eq!(layouts.len(), 2);
     assert_eq!(layouts[0].name, "BTreeWrapper");
+    assert_eq!(layouts[1].name, "HashWrapper");
 
     let mut btree0 = BTreeMap::<[u8; 32], Pubkey>::new();
     let mut id = [0; 32];
@@ -98,3 +112,88 @@ fn generate_layout_from_this_file() {
         assert_eq!(val, val_de);
     }
 }
+
+#[test]
+fn hashed_collections_decode_deterministically() {
+    let layouts = generate_layout_from_file("src/test/borsh_btree.rs").unwrap();
+    assert_eq!(
+        layouts[1].fields[0].ty(),
+        &BorshType::Map(Box::new(BorshType::U16), Box::new(BorshType::String))
+    );
+    assert_eq!(
+        layouts[1].fields[1].ty(),
+        &BorshType::Set(Box::new(BorshType::FixedBytes(4)))
+    );
+    assert_eq!(
+        layouts[1].fields[2].ty(),
+        &BorshType::Set(Box::new(BorshType::String))
+    );
+    // sets are serialized like vectors, which borsh-js reads into arrays
+    let schema = layouts[1].to_borsh_schema().unwrap();
+    assert!(schema.contains("['set', [[4]]],"));
+    assert!(schema.contains("['orderedSet', ['string']],"));
+    let ts_class = layouts[1].to_ts_class().unwrap();
+    assert!(ts_class.contains("  orderedSet: string[] | undefined;"));
+
+    let entries = [(300, "c"), (1, "a"), (20, "b")];
+    let set_items = [[9, 9, 9, 9], [0, 1, 2, 3], [5, 0, 0, 0]];
+
+    // insertion order doesn't matter, the serialized bytes are identical
+    let forward = HashWrapper {
+        map: entries.iter().map(|(k, v)| (*k, v.to_string())).collect(),
+        set: set_items.iter().copied().collect(),
+        ordered_set: ["zulu", "alpha"].iter().map(|s| s.to_string()).collect(),
+    };
+    let backward = HashWrapper {
+        map: entries
+            .iter()
+            .rev()
+            .map(|(k, v)| (*k, v.to_string()))
+            .collect(),
+        set: set_items.iter().rev().copied().collect(),
+        ordered_set: ["alpha", "zulu"].iter().map(|s| s.to_string()).collect(),
+    };
+    let data = forward.try_to_vec().unwrap();
+    assert_eq!(data, backward.try_to_vec().unwrap());
+
+    // and they match the serialized form of the ordered collections
+    let ordered = (
+        entries
+            .iter()
+            .map(|(k, v)| (*k, v.to_string()))
+            .collect::<BTreeMap<u16, String>>(),
+        set_items.iter().copied().collect::<BTreeSet<[u8; 4]>>(),
+        forward.ordered_set.clone(),
+    );
+    assert_eq!(data, ordered.try_to_vec().unwrap());
+
+    let decoded = Decoder::new(&layouts, &data).decode_layout("HashWrapper");
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            (
+                "map".to_owned(),
+                Value::Map(vec![
+                    (Value::Unsigned(1), Value::String("a".to_owned())),
+                    (Value::Unsigned(20), Value::String("b".to_owned())),
+                    (Value::Unsigned(300), Value::String("c".to_owned())),
+                ])
+            ),
+            (
+                "set".to_owned(),
+                Value::Seq(vec![
+                    Value::Bytes(vec![0, 1, 2, 3]),
+                    Value::Bytes(vec![5, 0, 0, 0]),
+                    Value::Bytes(vec![9, 9, 9, 9]),
+                ])
+            ),
+            (
+                "orderedSet".to_owned(),
+                Value::Seq(vec![
+                    Value::String("alpha".to_owned()),
+                    Value::String("zulu".to_owned()),
+                ])
+            ),
+        ])
+    );
+}
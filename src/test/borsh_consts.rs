@@ -0,0 +1,68 @@
+use super::decode::{Decoder, Value};
+use crate::layout::BorshType;
+use crate::{generate_layout_from_file, BorshSchemaTS};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use std::mem::size_of;
+
+const MAX_NAME_LEN: usize = 16;
+const N_SIGNERS: usize = MAX_NAME_LEN / 8;
+const HEADER_LEN: usize = 2 * size_of::<u32>() + 1;
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct ConstArrays {
+    name: [u8; MAX_NAME_LEN],
+    signers: [Pubkey; N_SIGNERS],
+    header: [u8; HEADER_LEN],
+    flags: [bool; N_SIGNERS + 1],
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_consts.rs").unwrap();
+    assert_eq!(layouts.len(), 1);
+    let types = layouts[0]
+        .fields
+        .iter()
+        .map(|field| field.ty().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            BorshType::FixedBytes(16),
+            BorshType::FixedArray(Box::new(BorshType::Pubkey), 2),
+            BorshType::FixedBytes(9),
+            BorshType::FixedArray(Box::new(BorshType::Bool), 3),
+        ]
+    );
+
+    let const_arrays = ConstArrays {
+        name: [7; MAX_NAME_LEN],
+        signers: [Pubkey::new_from_array([1; 32]); N_SIGNERS],
+        header: [0; HEADER_LEN],
+        flags: [true, false, true],
+    };
+    let data = const_arrays.try_to_vec().unwrap();
+    let decoded = Decoder::new(&layouts, &data).decode_layout("ConstArrays");
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            ("name".to_owned(), Value::Bytes(vec![7; 16])),
+            (
+                "signers".to_owned(),
+                Value::Seq(vec![Value::Bytes(vec![1; 32]), Value::Bytes(vec![1; 32])])
+            ),
+            ("header".to_owned(), Value::Bytes(vec![0; 9])),
+            (
+                "flags".to_owned(),
+                Value::Seq(vec![
+                    Value::Bool(true),
+                    Value::Bool(false),
+                    Value::Bool(true)
+                ])
+            ),
+        ])
+    );
+}
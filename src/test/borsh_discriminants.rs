@@ -0,0 +1,103 @@
+use super::decode::{Decoder, Value};
+use super::layouts_from_source;
+use crate::{generate_layout_from_file, BorshSchemaTS};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+// borsh-rs 0.9 ignores explicit discriminants and serializes the position
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub enum Positional {
+    First = 3,
+    Second = 7,
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_discriminants.rs").unwrap();
+    assert_eq!(layouts[0].name, "Positional");
+    assert_eq!(layouts[0].discriminants, vec![0, 1]);
+    assert!(!layouts[0].to_borsh_schema().unwrap().contains("unused"));
+
+    let data = Positional::Second.try_to_vec().unwrap();
+    assert_eq!(data, vec![1]);
+    let decoded = Decoder::new(&layouts, &data).decode_layout("Positional");
+    assert_eq!(
+        decoded,
+        Value::Enum(
+            "positionalSecond".to_owned(),
+            Box::new(Value::Struct(vec![]))
+        )
+    );
+}
+
+#[test]
+fn explicit_discriminants() {
+    let layouts = layouts_from_source(
+        "const BASE: u8 = 10;
+        #[derive(BorshSchemaTS)]
+        #[borsh(use_discriminant = true)]
+        pub enum Explicit {
+            Low = 1,
+            Next,
+            High = BASE + 2,
+            Payload(u16),
+        }",
+    )
+    .unwrap();
+    assert_eq!(layouts[0].discriminants, vec![1, 2, 12, 13]);
+    let schema = layouts[0].to_borsh_schema().unwrap();
+    assert!(schema.contains("\t\t\t['unused0', 'unused'],\n\t\t\t['explicitLow', ExplicitLow],"));
+    assert!(schema.contains("['unused11', 'unused'],\n\t\t\t['explicitHigh', ExplicitHigh],"));
+    assert!(schema
+        .contains("['explicitHigh', ExplicitHigh],\n\t\t\t['explicitPayload', ExplicitPayload],"));
+    assert_eq!(schema.matches("'unused'").count(), 10);
+
+    // borsh-rs 1.x with `use_discriminant = true` writes the discriminant
+    let data = vec![13, 0x34, 0x12];
+    let decoded = Decoder::new(&layouts, &data).decode_layout("Explicit");
+    assert_eq!(
+        decoded,
+        Value::Enum(
+            "explicitPayload".to_owned(),
+            Box::new(Value::Struct(vec![(
+                "unnamed_0".to_owned(),
+                Value::Unsigned(0x1234)
+            )]))
+        )
+    );
+
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        #[borsh(use_discriminant = false)]
+        pub enum Positional { A = 5, B = 1 }",
+    )
+    .unwrap();
+    assert_eq!(layouts[0].discriminants, vec![0, 1]);
+}
+
+#[test]
+fn unrepresentable_discriminants() {
+    let too_large = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        #[borsh(use_discriminant = true)]
+        pub enum TooLarge { A = 256 }",
+    );
+    assert!(too_large.is_err());
+
+    let overflowing = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        #[borsh(use_discriminant = true)]
+        pub enum Overflowing { A = 255, B }",
+    );
+    assert!(overflowing
+        .unwrap_err()
+        .to_string()
+        .contains("Overflowing::B"));
+
+    let negative = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        #[borsh(use_discriminant = true)]
+        pub enum Negative { A = -1 }",
+    );
+    assert!(negative.unwrap_err().to_string().contains("negative"));
+}
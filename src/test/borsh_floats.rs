@@ -0,0 +1,150 @@
+use super::decode::{Decoder, Value};
+use super::{layouts_from_source, TEST_DATA_DIRECTORY};
+use crate::layout::BorshType;
+use crate::{generate_layout_from_file, generate_output, BorshSchemaTS};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+
+use std::fs;
+use std::io::Write;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestData {
+    oracle_price: Vec<u8>,
+}
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct OraclePrice {
+    price: f64,
+    confidence: f32,
+    previous_price: Option<f64>,
+    history: Vec<f32>,
+    slot: u64,
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_floats.rs").unwrap();
+    assert_eq!(layouts.len(), 1);
+    assert_eq!(layouts[0].name, "OraclePrice");
+    assert_eq!(layouts[0].fields[0].ty(), &BorshType::F64);
+    assert_eq!(layouts[0].fields[1].ty(), &BorshType::F32);
+    assert!(layouts[0].has_float_fields());
+
+    let ts_class = layouts[0].to_ts_class().unwrap();
+    assert!(ts_class.contains("rejectNaN(this.price, 'price');"));
+    assert!(ts_class.contains("rejectNaN(this.history, 'history');"));
+    assert!(!ts_class.contains("rejectNaN(this.slot, 'slot');"));
+
+    let oracle_price = OraclePrice {
+        price: -1234.5678,
+        confidence: 0.25,
+        previous_price: Some(f64::INFINITY),
+        history: vec![1.5, -0.0, f32::MIN_POSITIVE],
+        slot: 1234567890,
+    };
+    let data = oracle_price.try_to_vec().unwrap();
+    assert_eq!(OraclePrice::try_from_slice(&data).unwrap(), oracle_price);
+
+    let decoded = Decoder::new(&layouts, &data).decode_layout("OraclePrice");
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            ("price".to_owned(), Value::Float(-1234.5678)),
+            ("confidence".to_owned(), Value::Float(0.25)),
+            (
+                "previousPrice".to_owned(),
+                Value::Option(Some(Box::new(Value::Float(f64::INFINITY))))
+            ),
+            (
+                "history".to_owned(),
+                Value::Seq(vec![
+                    Value::Float(1.5),
+                    Value::Float(-0.0),
+                    Value::Float(f32::MIN_POSITIVE as f64),
+                ])
+            ),
+            ("slot".to_owned(), Value::Unsigned(1234567890)),
+        ])
+    );
+
+    // borsh-rs refuses to deserialize NaN values, the generated TypeScript
+    // classes do the same
+    let mut nan_data = data.clone();
+    nan_data[..8].copy_from_slice(&f64::NAN.to_le_bytes());
+    assert!(OraclePrice::try_from_slice(&nan_data).is_err());
+
+    let test_data = TestData { oracle_price: data };
+
+    fs::create_dir_all(TEST_DATA_DIRECTORY).unwrap();
+    let mut file =
+        fs::File::create(String::from(TEST_DATA_DIRECTORY) + "/test_floats.json").unwrap();
+    write!(file, "{}", serde_json::to_string(&test_data).unwrap()).unwrap();
+}
+
+/// Returns the body of every class in the generated code by name.
+fn class_bodies(output: &str) -> Vec<(&str, &str)> {
+    output
+        .split("export class ")
+        .skip(1)
+        .map(|class| {
+            let name = class.split(' ').next().unwrap();
+            let end = class.find("\n};\n").unwrap();
+            (name, &class[..end])
+        })
+        .collect()
+}
+
+#[test]
+fn nan_guard_in_float_constructors() {
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        struct Reading { value: f32, samples: Vec<Option<f64>>, sensor: u8 }
+        #[derive(BorshSchemaTS)]
+        enum Event { Measured(Reading, f64), Calibrated { offset: f32 }, Reset }
+        #[derive(BorshSchemaTS)]
+        struct Counter { count: u64 }",
+    )
+    .unwrap();
+    let directory = "test-output/floats";
+    generate_output(&layouts, directory).unwrap();
+    let output = fs::read_to_string(format!("{}/schema.ts", directory)).unwrap();
+
+    let helper = output.find("function rejectNaN(").unwrap();
+    assert!(helper < output.find("export class").unwrap());
+
+    let guards = [
+        ("Reading", vec!["value", "samples"]),
+        ("EventMeasured", vec!["unnamed_1"]),
+        ("EventCalibrated", vec!["offset"]),
+    ];
+    let classes = class_bodies(&output);
+    assert_eq!(classes.len(), 6);
+    for (name, body) in classes {
+        match guards.iter().find(|(guarded, _)| *guarded == name) {
+            Some((_, fields)) => {
+                let checks = fields
+                    .iter()
+                    .map(|field| format!("\n    rejectNaN(this.{0}, '{0}');", field))
+                    .collect::<String>();
+                let constructor = format!(
+                    "\n  constructor(properties: any) {{\n    super(properties);{}\n  }}",
+                    checks
+                );
+                assert!(
+                    body.ends_with(&constructor),
+                    "missing NaN guard in `{}`:\n{}",
+                    name,
+                    body
+                );
+            }
+            None => assert!(
+                !body.contains("constructor"),
+                "unexpected guard in `{}`",
+                name
+            ),
+        }
+    }
+}
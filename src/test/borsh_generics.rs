@@ -0,0 +1,276 @@
+use super::decode::{Decoder, Value};
+use super::layouts_from_source;
+use crate::layout::{monomorphize, BorshType};
+use crate::{
+    generate_layout_from_file, generate_output, generate_output_with_options, BorshSchemaTS,
+    GeneratorOptions, GenericsMode,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use std::fs;
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct Wrapper<T> {
+    inner: Vec<T>,
+    count: u8,
+}
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub enum Either<L, R> {
+    Left(L),
+    Right { value: R },
+}
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct Holder {
+    numbers: Wrapper<u64>,
+    keys: Wrapper<Pubkey>,
+    nested: Option<Wrapper<Wrapper<u8>>>,
+    choice: Either<u8, String>,
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_generics.rs").unwrap();
+    assert_eq!(layouts.len(), 5);
+    assert_eq!(layouts[0].name, "Wrapper");
+    assert_eq!(layouts[0].generics, vec!["T".to_owned()]);
+    assert_eq!(
+        layouts[0].fields[0].ty(),
+        &BorshType::Vec(Box::new(BorshType::TypeParam("T".to_owned())))
+    );
+    assert_eq!(layouts[1].name, "Either");
+    assert_eq!(layouts[1].generics, vec!["L".to_owned(), "R".to_owned()]);
+    assert_eq!(layouts[2].name, "EitherLeft");
+    assert_eq!(layouts[3].name, "EitherRight");
+    assert_eq!(layouts[4].name, "Holder");
+    assert!(!layouts[4].is_generic());
+    assert_eq!(
+        layouts[4].fields[0].ty(),
+        &BorshType::Generic("Wrapper".to_owned(), vec![BorshType::U64])
+    );
+
+    let monomorphized = monomorphize(&layouts).unwrap();
+    let names = monomorphized
+        .iter()
+        .map(|layout| layout.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "Holder",
+            "WrapperU64",
+            "WrapperPubkey",
+            "WrapperWrapperU8",
+            "WrapperU8",
+            "EitherU8_String",
+            "EitherLeftU8_String",
+            "EitherRightU8_String",
+        ]
+    );
+    assert!(monomorphized.iter().all(|layout| !layout.is_generic()));
+
+    let holder = Holder {
+        numbers: Wrapper {
+            inner: vec![1, 2],
+            count: 2,
+        },
+        keys: Wrapper {
+            inner: vec![],
+            count: 0,
+        },
+        nested: Some(Wrapper {
+            inner: vec![Wrapper {
+                inner: vec![9],
+                count: 1,
+            }],
+            count: 1,
+        }),
+        choice: Either::Right {
+            value: "right".to_owned(),
+        },
+    };
+    let data = holder.try_to_vec().unwrap();
+    let decoded = Decoder::new(&monomorphized, &data).decode_layout("Holder");
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            (
+                "numbers".to_owned(),
+                Value::Struct(vec![
+                    (
+                        "inner".to_owned(),
+                        Value::Seq(vec![Value::Unsigned(1), Value::Unsigned(2)])
+                    ),
+                    ("count".to_owned(), Value::Unsigned(2)),
+                ])
+            ),
+            (
+                "keys".to_owned(),
+                Value::Struct(vec![
+                    ("inner".to_owned(), Value::Seq(vec![])),
+                    ("count".to_owned(), Value::Unsigned(0)),
+                ])
+            ),
+            (
+                "nested".to_owned(),
+                Value::Option(Some(Box::new(Value::Struct(vec![
+                    (
+                        "inner".to_owned(),
+                        Value::Seq(vec![Value::Struct(vec![
+                            ("inner".to_owned(), Value::Seq(vec![Value::Unsigned(9)])),
+                            ("count".to_owned(), Value::Unsigned(1)),
+                        ])])
+                    ),
+                    ("count".to_owned(), Value::Unsigned(1)),
+                ]))))
+            ),
+            (
+                "choice".to_owned(),
+                Value::Enum(
+                    "eitherRight".to_owned(),
+                    Box::new(Value::Struct(vec![(
+                        "value".to_owned(),
+                        Value::String("right".to_owned())
+                    )]))
+                )
+            ),
+        ])
+    );
+}
+
+/// Checks that every class referenced by the entries of the `SCHEMA` has an
+/// entry itself, otherwise `borsh-js` fails to (de)serialize it.
+fn assert_schema_complete(output: &str) {
+    let schema = &output[output.find("export const SCHEMA").unwrap()..];
+    let mut registered = Vec::new();
+    let mut referenced = Vec::new();
+    for line in schema.lines() {
+        match line.strip_prefix("\t\t\t[") {
+            // the schema of a field, e.g. `['numbers', WrapperU64],`
+            Some(entry) => referenced.extend(
+                entry[entry.find(", ").unwrap()..]
+                    .split(|c: char| !c.is_alphanumeric() && c != '_')
+                    .filter(|word| word.starts_with(char::is_uppercase))
+                    .map(str::to_owned),
+            ),
+            // the class of an entry
+            None => {
+                let line = line.trim();
+                if line.starts_with(char::is_uppercase) {
+                    registered.push(line.trim_end_matches(',').to_owned());
+                }
+            }
+        }
+    }
+    assert!(!referenced.is_empty());
+    for class in referenced {
+        assert!(registered.contains(&class), "`{}` has no schema", class);
+    }
+}
+
+#[test]
+fn generate_monomorphized_output() {
+    let layouts = generate_layout_from_file("src/test/borsh_generics.rs").unwrap();
+    generate_output(&layouts, "test-output/generics/monomorphized").unwrap();
+    let output = fs::read_to_string("test-output/generics/monomorphized/schema.ts").unwrap();
+    assert!(output.contains("  numbers: WrapperU64 | undefined;"));
+    assert!(output.contains("\t\t\t['nested', { kind: 'option', type: WrapperWrapperU8 }],"));
+    assert_schema_complete(&output);
+}
+
+#[test]
+fn generate_generic_output() {
+    let layouts = generate_layout_from_file("src/test/borsh_generics.rs").unwrap();
+    let options = GeneratorOptions {
+        generics: GenericsMode::Generic,
+        ..GeneratorOptions::default()
+    };
+    // `Holder` would refer to classes without a schema
+    let err = generate_output_with_options(&layouts, "test-output/generics/generic", &options)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("  `Wrapper` used by `Holder.numbers`\n"));
+    assert!(err.contains("  `Wrapper` used by `Holder.nested`\n"));
+    assert!(err.contains("  `Either` used by `Holder.choice`"));
+
+    let generic_layouts = layouts
+        .into_iter()
+        .filter(|layout| layout.is_generic())
+        .collect::<Vec<_>>();
+    generate_output_with_options(&generic_layouts, "test-output/generics/generic", &options)
+        .unwrap();
+    let output = fs::read_to_string("test-output/generics/generic/schema.ts").unwrap();
+    assert!(output.contains("export class Wrapper<T> extends Struct {"));
+    assert!(output.contains("  inner: T[] | undefined;"));
+    assert!(output.contains("export class Either<L, R> extends Enum {"));
+    assert!(output.contains("  eitherLeft: EitherLeft<L, R> | undefined;"));
+    assert!(output.contains("export const wrapperSchema = (T: any): any => ({"));
+    assert!(output.contains("    ['inner', [T]],"));
+    // generic layouts are registered manually with their schema factories
+    let schema = &output[output.find("export const SCHEMA").unwrap()..];
+    assert!(!schema.contains("Wrapper"));
+    assert!(!schema.contains("Either"));
+}
+
+#[test]
+fn distinct_instantiation_names() {
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)] struct Pair<A, B> { a: A, b: B }
+        #[derive(BorshSchemaTS)] struct Foo { x: u8 }
+        #[derive(BorshSchemaTS)] struct FooBar { x: u16 }
+        #[derive(BorshSchemaTS)] struct Bar { x: u32 }
+        #[derive(BorshSchemaTS)] struct Baz { x: u64 }
+        #[derive(BorshSchemaTS)] struct BarBaz { x: u128 }
+        #[derive(BorshSchemaTS)]
+        struct Holder { one: Pair<FooBar, Baz>, two: Pair<Foo, BarBaz> }",
+    )
+    .unwrap();
+    let monomorphized = monomorphize(&layouts).unwrap();
+    let holder = monomorphized
+        .iter()
+        .find(|layout| layout.name == "Holder")
+        .unwrap();
+    assert_eq!(
+        holder.fields[0].ty(),
+        &BorshType::Custom("PairFooBar_Baz".to_owned())
+    );
+    assert_eq!(
+        holder.fields[1].ty(),
+        &BorshType::Custom("PairFoo_BarBaz".to_owned())
+    );
+    let pair = monomorphized
+        .iter()
+        .find(|layout| layout.name == "PairFoo_BarBaz")
+        .unwrap();
+    assert_eq!(pair.fields[0].ty(), &BorshType::Custom("Foo".to_owned()));
+    assert_eq!(pair.fields[1].ty(), &BorshType::Custom("BarBaz".to_owned()));
+
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)] struct Pair<A, B> { a: A, b: B }
+        #[derive(BorshSchemaTS)] struct Foo_Bar { x: u8 }
+        #[derive(BorshSchemaTS)] struct Bar_Baz { x: u8 }
+        #[derive(BorshSchemaTS)]
+        struct Holder { one: Pair<Foo_Bar, Baz>, two: Pair<Foo, Bar_Baz> }",
+    )
+    .unwrap();
+    assert_eq!(
+        monomorphize(&layouts).unwrap_err().to_string(),
+        "can't emit an instantiation of `Pair` as `PairFoo_Bar_Baz`, \
+        because another instantiation is emitted with that name"
+    );
+
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)] struct Wrapper<T> { inner: T }
+        #[derive(BorshSchemaTS)] struct WrapperU64 { inner: u8 }
+        #[derive(BorshSchemaTS)] struct Holder { wrapper: Wrapper<u64> }",
+    )
+    .unwrap();
+    assert_eq!(
+        monomorphize(&layouts).unwrap_err().to_string(),
+        "can't emit an instantiation of `Wrapper` as `WrapperU64`, \
+        because a layout with that name already exists"
+    );
+}
@@ -0,0 +1,270 @@
+use super::decode::{Decoder, Value};
+use super::{layouts_from_source, write_files};
+use crate::layout::BorshType;
+use crate::{generate_layout_from_file, generate_layouts, generate_output};
+
+use borsh::BorshSerialize;
+
+pub mod state {
+    use crate::BorshSchemaTS;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+    pub struct Config {
+        pub admin: [u8; 4],
+        pub fee: u16,
+    }
+
+    #[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+    pub struct Account {
+        pub config: Config,
+        pub record: records::Record,
+    }
+
+    pub mod records {
+        use crate::BorshSchemaTS;
+        use borsh::{BorshDeserialize, BorshSerialize};
+
+        #[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+        pub struct Record {
+            pub id: u8,
+            pub config: super::Config,
+        }
+    }
+}
+
+pub mod instruction {
+    use crate::BorshSchemaTS;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+    pub struct Config {
+        pub fee: u16,
+    }
+
+    #[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+    pub enum Instruction {
+        Initialize(Config),
+        Close,
+    }
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_modules.rs").unwrap();
+    let names = layouts
+        .iter()
+        .map(|layout| (layout.module_path.join("::"), layout.name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("state".to_owned(), "StateConfig"),
+            ("state".to_owned(), "Account"),
+            ("state::records".to_owned(), "Record"),
+            ("instruction".to_owned(), "InstructionConfig"),
+            ("instruction".to_owned(), "Instruction"),
+            ("instruction".to_owned(), "InstructionInitialize"),
+            ("instruction".to_owned(), "InstructionClose"),
+        ]
+    );
+    assert_eq!(
+        layouts[1].fields[0].ty(),
+        &BorshType::Custom("StateConfig".to_owned())
+    );
+    // resolved to the closest parent module
+    assert_eq!(
+        layouts[2].fields[1].ty(),
+        &BorshType::Custom("StateConfig".to_owned())
+    );
+    assert_eq!(
+        layouts[5].fields[0].ty(),
+        &BorshType::Custom("InstructionConfig".to_owned())
+    );
+
+    let config = state::Config {
+        admin: [1, 2, 3, 4],
+        fee: 5,
+    };
+    let account = state::Account {
+        config: config.clone(),
+        record: state::records::Record { id: 9, config },
+    };
+    let data = account.try_to_vec().unwrap();
+    let decoded = Decoder::new(&layouts, &data).decode_layout("Account");
+    let decoded_config = Value::Struct(vec![
+        ("admin".to_owned(), Value::Bytes(vec![1, 2, 3, 4])),
+        ("fee".to_owned(), Value::Unsigned(5)),
+    ]);
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            ("config".to_owned(), decoded_config.clone()),
+            (
+                "record".to_owned(),
+                Value::Struct(vec![
+                    ("id".to_owned(), Value::Unsigned(9)),
+                    ("config".to_owned(), decoded_config),
+                ])
+            ),
+        ])
+    );
+
+    let data = instruction::Instruction::Initialize(instruction::Config { fee: 7 })
+        .try_to_vec()
+        .unwrap();
+    let decoded = Decoder::new(&layouts, &data).decode_layout("Instruction");
+    assert_eq!(
+        decoded,
+        Value::Enum(
+            "instructionInitialize".to_owned(),
+            Box::new(Value::Struct(vec![(
+                "unnamed_0".to_owned(),
+                Value::Struct(vec![("fee".to_owned(), Value::Unsigned(7))])
+            )]))
+        )
+    );
+}
+
+#[test]
+fn ambiguous_module_references() {
+    let err = layouts_from_source(
+        "mod a { #[derive(BorshSchemaTS)] struct Config { x: u8 } }
+        mod b { #[derive(BorshSchemaTS)] struct Config { y: u8 } }
+        #[derive(BorshSchemaTS)]
+        struct Root { config: Config }",
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "ambiguous reference to `Config` in `Root`, it is declared as `a::Config`, `b::Config`"
+    );
+
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)] struct Config { x: u8 }
+        mod b { #[derive(BorshSchemaTS)] struct Config { y: u8 } }
+        #[derive(BorshSchemaTS)]
+        struct Root { config: Option<Config> }",
+    )
+    .unwrap();
+    assert_eq!(layouts[0].name, "Config");
+    assert_eq!(layouts[1].name, "BConfig");
+    assert_eq!(
+        layouts[2].fields[0].ty(),
+        &BorshType::Option(Box::new(BorshType::Custom("Config".to_owned())))
+    );
+
+    let err = layouts_from_source(
+        "mod a { #[derive(BorshSchemaTS)] struct Config { x: u8 } }
+        mod b { #[derive(BorshSchemaTS)] struct Config { y: u8 } }
+        #[derive(BorshSchemaTS)]
+        struct AConfig { z: u8 }",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("`a::Config` to `AConfig`"));
+}
+
+#[test]
+fn aliases_and_layouts_with_the_same_name() {
+    let layouts = layouts_from_source(
+        "mod legacy { pub type Account = [u8; 64]; }
+        mod state {
+            #[derive(BorshSchemaTS)] pub struct Account { x: u8 }
+            #[derive(BorshSchemaTS)] struct Holder { account: Account }
+        }
+        mod old {
+            use crate::legacy::Account;
+            #[derive(BorshSchemaTS)] struct Old { account: Account }
+        }
+        #[derive(BorshSchemaTS)]
+        struct Root { legacy: legacy::Account, account: state::Account }",
+    )
+    .unwrap();
+    let names = layouts
+        .iter()
+        .map(|layout| layout.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Account", "Holder", "Old", "Root"]);
+    assert_eq!(
+        layouts[1].fields[0].ty(),
+        &BorshType::Custom("Account".to_owned())
+    );
+    assert_eq!(layouts[2].fields[0].ty(), &BorshType::FixedBytes(64));
+    assert_eq!(layouts[3].fields[0].ty(), &BorshType::FixedBytes(64));
+    assert_eq!(
+        layouts[3].fields[1].ty(),
+        &BorshType::Custom("Account".to_owned())
+    );
+}
+
+#[test]
+fn scanned_files_are_modules() {
+    let root = write_files(
+        "modules",
+        &[
+            (
+                "mod.rs",
+                "#[derive(BorshSchemaTS)]
+                struct Root { account: state::Account }",
+            ),
+            (
+                "config.rs",
+                "#[derive(BorshSchemaTS)] struct Config { x: u8 }",
+            ),
+            (
+                "state/mod.rs",
+                "#[derive(BorshSchemaTS)] struct Config { y: u16 }",
+            ),
+            (
+                "state/account.rs",
+                "#[derive(BorshSchemaTS)] struct Account { config: Config }",
+            ),
+        ],
+    );
+    let layouts = generate_layouts(&root).unwrap();
+    let find = |name: &str| {
+        layouts
+            .iter()
+            .find(|layout| layout.name == name)
+            .unwrap_or_else(|| panic!("no layout named `{}`", name))
+    };
+
+    assert_eq!(find("ConfigConfig").module_path, vec!["config"]);
+    assert_eq!(find("StateConfig").module_path, vec!["state"]);
+    assert_eq!(find("Account").module_path, vec!["state", "account"]);
+    assert!(find("Root").module_path.is_empty());
+    assert_eq!(
+        find("Account").fields[0].ty(),
+        &BorshType::Custom("StateConfig".to_owned())
+    );
+
+    let output = root.join("output");
+    generate_output(&layouts, &output).unwrap();
+    let schema = std::fs::read_to_string(output.join("schema.ts")).unwrap();
+    assert_eq!(schema.matches("export class ConfigConfig ").count(), 1);
+    assert_eq!(schema.matches("export class StateConfig ").count(), 1);
+    assert!(!schema.contains("export class Config "));
+}
+
+#[test]
+fn duplicate_layouts_in_a_module() {
+    let root = write_files(
+        "duplicates",
+        &[
+            ("lib.rs", "#[derive(BorshSchemaTS)] struct Config { x: u8 }"),
+            (
+                "main.rs",
+                "#[derive(BorshSchemaTS)] struct Config { y: u8 }",
+            ),
+        ],
+    );
+    let err = generate_layouts(&root).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "`Config` is declared more than once, in `{}` and `{}`",
+            root.join("lib.rs").display(),
+            root.join("main.rs").display()
+        )
+    );
+}
@@ -0,0 +1,216 @@
+use super::decode::{Decoder, Value};
+use super::layouts_from_source;
+use super::TEST_DATA_DIRECTORY;
+use crate::layout::{monomorphize, validate, BorshType};
+use crate::{generate_layout_from_file, BorshSchemaTS};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+
+use std::borrow::Cow;
+use std::fs;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestData {
+    tree_node: Vec<u8>,
+    shared_state: Vec<u8>,
+}
+
+#[derive(BorshSchemaTS, Clone, Debug, PartialEq)]
+pub struct TreeNode {
+    value: u64,
+    left: Option<Box<TreeNode>>,
+    right: Option<Box<Self>>,
+}
+
+// borsh-derive 0.9 can't handle recursive types, hence the manual impls
+impl BorshSerialize for TreeNode {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        BorshSerialize::serialize(&self.value, writer)?;
+        BorshSerialize::serialize(&self.left, writer)?;
+        BorshSerialize::serialize(&self.right, writer)
+    }
+}
+
+impl BorshDeserialize for TreeNode {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        Ok(Self {
+            value: BorshDeserialize::deserialize(buf)?,
+            left: BorshDeserialize::deserialize(buf)?,
+            right: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct SharedState {
+    root: Rc<TreeNode>,
+    history: Arc<Vec<u32>>,
+    label: Cow<'static, str>,
+    marker: PhantomData<u32>,
+    version: u8,
+}
+
+fn leaf(value: u64) -> Option<Box<TreeNode>> {
+    Some(Box::new(TreeNode {
+        value,
+        left: None,
+        right: None,
+    }))
+}
+
+fn decoded_leaf(value: u128) -> Value {
+    Value::Option(Some(Box::new(Value::Struct(vec![
+        ("value".to_owned(), Value::Unsigned(value)),
+        ("left".to_owned(), Value::Option(None)),
+        ("right".to_owned(), Value::Option(None)),
+    ]))))
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_pointers.rs").unwrap();
+    assert_eq!(layouts.len(), 2);
+    assert_eq!(layouts[0].name, "TreeNode");
+    assert_eq!(layouts[1].name, "SharedState");
+
+    assert_eq!(
+        layouts[0].fields[1].ty(),
+        &BorshType::Option(Box::new(BorshType::Custom("TreeNode".to_owned())))
+    );
+    // `Self` is the layout itself
+    assert_eq!(layouts[0].fields[2].ty(), layouts[0].fields[1].ty());
+    assert_eq!(
+        layouts[1].fields[0].ty(),
+        &BorshType::Custom("TreeNode".to_owned())
+    );
+    assert_eq!(
+        layouts[1].fields[1].ty(),
+        &BorshType::Vec(Box::new(BorshType::U32))
+    );
+    assert_eq!(layouts[1].fields[2].ty(), &BorshType::String);
+    assert!(layouts[1].fields[3].should_skip());
+    assert!(!layouts[1].to_ts_class().unwrap().contains("marker"));
+    assert!(!layouts[1].to_borsh_schema().unwrap().contains("marker"));
+
+    let tree_node = TreeNode {
+        value: 1,
+        left: leaf(2),
+        right: Some(Box::new(TreeNode {
+            value: 3,
+            left: leaf(4),
+            right: None,
+        })),
+    };
+
+    let shared_state = SharedState {
+        root: Rc::new(tree_node.clone()),
+        history: Arc::new(vec![10, 20]),
+        label: Cow::Borrowed("shared"),
+        marker: PhantomData,
+        version: 7,
+    };
+
+    let data = shared_state.try_to_vec().unwrap();
+    assert_eq!(SharedState::try_from_slice(&data).unwrap(), shared_state);
+
+    let decoded = Decoder::new(&layouts, &data).decode_layout("SharedState");
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            (
+                "root".to_owned(),
+                Value::Struct(vec![
+                    ("value".to_owned(), Value::Unsigned(1)),
+                    ("left".to_owned(), decoded_leaf(2)),
+                    (
+                        "right".to_owned(),
+                        Value::Option(Some(Box::new(Value::Struct(vec![
+                            ("value".to_owned(), Value::Unsigned(3)),
+                            ("left".to_owned(), decoded_leaf(4)),
+                            ("right".to_owned(), Value::Option(None)),
+                        ]))))
+                    ),
+                ])
+            ),
+            (
+                "history".to_owned(),
+                Value::Seq(vec![Value::Unsigned(10), Value::Unsigned(20)])
+            ),
+            ("label".to_owned(), Value::String("shared".to_owned())),
+            ("version".to_owned(), Value::Unsigned(7)),
+        ])
+    );
+
+    let test_data = TestData {
+        tree_node: tree_node.try_to_vec().unwrap(),
+        shared_state: data,
+    };
+
+    fs::create_dir_all(TEST_DATA_DIRECTORY).unwrap();
+    let mut file =
+        fs::File::create(String::from(TEST_DATA_DIRECTORY) + "/test_pointers.json").unwrap();
+    write!(file, "{}", serde_json::to_string(&test_data).unwrap()).unwrap();
+}
+
+#[test]
+fn recursive_layouts_refer_to_self() {
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        struct Node { children: Vec<Self> }
+        #[derive(BorshSchemaTS)]
+        enum Expr { Neg(Box<Self>), Sum { terms: Vec<Self> }, Leaf(Node) }
+        #[derive(BorshSchemaTS)]
+        struct Tree<T> { value: T, children: Vec<Self> }
+        #[derive(BorshSchemaTS)]
+        struct Forest { trees: Vec<Tree<u8>> }",
+    )
+    .unwrap();
+    validate(&layouts).unwrap();
+    assert_eq!(
+        layouts[0].fields[0].ty(),
+        &BorshType::Vec(Box::new(BorshType::Custom("Node".to_owned())))
+    );
+    // in the variants `Self` is the `enum`, not the variant
+    assert_eq!(layouts[1].name, "Expr");
+    assert_eq!(
+        layouts[2].fields[0].ty(),
+        &BorshType::Custom("Expr".to_owned())
+    );
+    assert_eq!(
+        layouts[3].fields[0].ty(),
+        &BorshType::Vec(Box::new(BorshType::Custom("Expr".to_owned())))
+    );
+    assert_eq!(
+        layouts[5].fields[1].ty(),
+        &BorshType::Vec(Box::new(BorshType::Generic(
+            "Tree".to_owned(),
+            vec![BorshType::TypeParam("T".to_owned())]
+        )))
+    );
+
+    let monomorphized = monomorphize(&layouts).unwrap();
+    validate(&monomorphized).unwrap();
+    let tree = monomorphized
+        .iter()
+        .find(|layout| layout.name == "TreeU8")
+        .unwrap();
+    assert_eq!(
+        tree.to_borsh_schema().unwrap(),
+        "
+    [
+            TreeU8,
+            {
+                kind: 'struct', fields: [
+\t\t\t['value', 'u8'],
+\t\t\t['children', [TreeU8]],
+                ],
+            },
+    ],"
+    );
+}
@@ -0,0 +1,95 @@
+use super::decode::{Decoder, Value};
+use super::TEST_DATA_DIRECTORY;
+use crate::layout::BorshType;
+use crate::{generate_layout_from_file, BorshSchemaTS};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+
+use std::fs;
+use std::io::Write;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestData {
+    signed_struct: Vec<u8>,
+}
+
+type UnixTimestamp = i64;
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct SignedStruct {
+    field_a: i8,
+    field_b: i16,
+    field_c: i32,
+    field_d: i64,
+    field_e: i128,
+    field_f: Vec<i64>,
+    timestamp: UnixTimestamp,
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_signed.rs").unwrap();
+    assert_eq!(layouts.len(), 1);
+    assert_eq!(layouts[0].name, "SignedStruct");
+    let types = layouts[0]
+        .fields
+        .iter()
+        .map(|field| field.ty().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            BorshType::I8,
+            BorshType::I16,
+            BorshType::I32,
+            BorshType::I64,
+            BorshType::I128,
+            BorshType::Vec(Box::new(BorshType::I64)),
+            BorshType::I64,
+        ]
+    );
+
+    let signed_struct = SignedStruct {
+        field_a: i8::MIN,
+        field_b: -2,
+        field_c: -123_456,
+        field_d: -1_234_567_890_123,
+        field_e: i128::MIN + 1,
+        field_f: vec![-1, 0, i64::MAX],
+        timestamp: -1_000_000,
+    };
+    let data = signed_struct.try_to_vec().unwrap();
+    assert_eq!(SignedStruct::try_from_slice(&data).unwrap(), signed_struct);
+
+    let decoded = Decoder::new(&layouts, &data).decode_layout("SignedStruct");
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            ("fieldA".to_owned(), Value::Signed(i8::MIN as i128)),
+            ("fieldB".to_owned(), Value::Signed(-2)),
+            ("fieldC".to_owned(), Value::Signed(-123_456)),
+            ("fieldD".to_owned(), Value::Signed(-1_234_567_890_123)),
+            ("fieldE".to_owned(), Value::Signed(i128::MIN + 1)),
+            (
+                "fieldF".to_owned(),
+                Value::Seq(vec![
+                    Value::Signed(-1),
+                    Value::Signed(0),
+                    Value::Signed(i64::MAX as i128),
+                ])
+            ),
+            ("timestamp".to_owned(), Value::Signed(-1_000_000)),
+        ])
+    );
+
+    let test_data = TestData {
+        signed_struct: data,
+    };
+
+    fs::create_dir_all(TEST_DATA_DIRECTORY).unwrap();
+    let mut file =
+        fs::File::create(String::from(TEST_DATA_DIRECTORY) + "/test_signed.json").unwrap();
+    write!(file, "{}", serde_json::to_string(&test_data).unwrap()).unwrap();
+}
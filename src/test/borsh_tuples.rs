@@ -0,0 +1,220 @@
+use super::decode::{Decoder, Value};
+use super::{layouts_from_source, TEST_DATA_DIRECTORY};
+use crate::layout::{lift_tuples, BorshType};
+use crate::{
+    generate_layout_from_file, generate_output, generate_output_with_options, BorshSchemaTS,
+    GeneratorOptions, GenericsMode,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+
+use std::fs;
+use std::io::Write;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestData {
+    tuple_fields: Vec<u8>,
+    tuple_enum_a: Vec<u8>,
+    tuple_enum_b: Vec<u8>,
+}
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct TupleFields {
+    pair: (u8, Pubkey),
+    mixed: (String, Vec<u64>),
+    unit: (),
+    nested: Vec<(u16, (i8, bool))>,
+}
+
+#[derive(BorshSchemaTS, BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub enum TupleEnum {
+    VariantA(u64, u8),
+    VariantB((u8, u8), i32),
+}
+
+/// The decoded value of a tuple struct with the given elements.
+fn elements(values: Vec<Value>) -> Value {
+    Value::Struct(
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (format!("unnamed_{}", i), value))
+            .collect(),
+    )
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_tuples.rs").unwrap();
+    assert_eq!(layouts.len(), 4);
+    assert_eq!(layouts[0].name, "TupleFields");
+    assert_eq!(layouts[1].name, "TupleEnum");
+    assert_eq!(layouts[2].name, "TupleEnumVariantA");
+    assert_eq!(layouts[3].name, "TupleEnumVariantB");
+
+    assert_eq!(
+        layouts[0].fields[0].ty(),
+        &BorshType::Tuple(vec![BorshType::U8, BorshType::Pubkey])
+    );
+    assert_eq!(layouts[0].fields[2].ty(), &BorshType::Tuple(vec![]));
+
+    // multi-field tuple variants get one positional field per element
+    let variant_fields = layouts[3]
+        .fields
+        .iter()
+        .map(|field| (field.name(), field.ty().clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        variant_fields,
+        vec![
+            (
+                "unnamed_0",
+                BorshType::Tuple(vec![BorshType::U8, BorshType::U8])
+            ),
+            ("unnamed_1", BorshType::I32),
+        ]
+    );
+
+    let mut pubkey_array = [0; 32];
+    pubkey_array[0] = 7;
+    let tuple_fields = TupleFields {
+        pair: (3, Pubkey::new(&pubkey_array)),
+        mixed: ("tuple".to_owned(), vec![1, 2]),
+        unit: (),
+        nested: vec![(500, (-5, true))],
+    };
+    let tuple_enum_a = TupleEnum::VariantA(1234567890, 12);
+    let tuple_enum_b = TupleEnum::VariantB((1, 2), -3);
+
+    // every tuple type is emitted as a struct with a field per element
+    let lifted = lift_tuples(&layouts).unwrap();
+    let names = lifted
+        .iter()
+        .map(|layout| layout.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "TupleFields",
+            "TupleEnum",
+            "TupleEnumVariantA",
+            "TupleEnumVariantB",
+            "TupleU8_Pubkey",
+            "TupleString_VecU64",
+            "Tuple",
+            "TupleU16_TupleI8_Bool",
+            "TupleI8_Bool",
+            "TupleU8_U8",
+        ]
+    );
+    assert_eq!(
+        lifted[0].fields[3].ty(),
+        &BorshType::Vec(Box::new(BorshType::Custom(
+            "TupleU16_TupleI8_Bool".to_owned()
+        )))
+    );
+    assert_eq!(
+        lifted[7].fields[1].ty(),
+        &BorshType::Custom("TupleI8_Bool".to_owned())
+    );
+    assert_eq!(
+        lifted[4].to_borsh_schema().unwrap(),
+        "
+    [
+            TupleU8_Pubkey,
+            {
+                kind: 'struct', fields: [
+\t\t\t['unnamed_0', 'u8'],
+\t\t\t['unnamed_1', PublicKeyBE],
+                ],
+            },
+    ],"
+    );
+
+    let data = tuple_fields.try_to_vec().unwrap();
+    let decoded = Decoder::new(&lifted, &data).decode_layout("TupleFields");
+    assert_eq!(
+        decoded,
+        Value::Struct(vec![
+            (
+                "pair".to_owned(),
+                elements(vec![
+                    Value::Unsigned(3),
+                    Value::Bytes(pubkey_array.to_vec())
+                ])
+            ),
+            (
+                "mixed".to_owned(),
+                elements(vec![
+                    Value::String("tuple".to_owned()),
+                    Value::Seq(vec![Value::Unsigned(1), Value::Unsigned(2)]),
+                ])
+            ),
+            ("unit".to_owned(), elements(vec![])),
+            (
+                "nested".to_owned(),
+                Value::Seq(vec![elements(vec![
+                    Value::Unsigned(500),
+                    elements(vec![Value::Signed(-5), Value::Bool(true)]),
+                ])])
+            ),
+        ])
+    );
+
+    let enum_data = tuple_enum_b.try_to_vec().unwrap();
+    let decoded = Decoder::new(&lifted, &enum_data).decode_layout("TupleEnum");
+    assert_eq!(
+        decoded,
+        Value::Enum(
+            "tupleEnumVariantB".to_owned(),
+            Box::new(Value::Struct(vec![
+                (
+                    "unnamed_0".to_owned(),
+                    elements(vec![Value::Unsigned(1), Value::Unsigned(2)])
+                ),
+                ("unnamed_1".to_owned(), Value::Signed(-3)),
+            ]))
+        )
+    );
+
+    let test_data = TestData {
+        tuple_fields: data,
+        tuple_enum_a: tuple_enum_a.try_to_vec().unwrap(),
+        tuple_enum_b: enum_data,
+    };
+
+    fs::create_dir_all(TEST_DATA_DIRECTORY).unwrap();
+    let mut file =
+        fs::File::create(String::from(TEST_DATA_DIRECTORY) + "/test_tuples.json").unwrap();
+    write!(file, "{}", serde_json::to_string(&test_data).unwrap()).unwrap();
+}
+
+#[test]
+fn tuples_of_generic_layouts() {
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        struct Pair<T> { pair: (T, u8) }
+        #[derive(BorshSchemaTS)]
+        struct Holder { pairs: Vec<Pair<u64>>, flat: (u64, u8) }",
+    )
+    .unwrap();
+    let directory = "test-output/tuples";
+    generate_output(&layouts, directory).unwrap();
+    let output = fs::read_to_string(format!("{}/schema.ts", directory)).unwrap();
+    // the tuple of the instantiation is the same type as the one in `Holder`
+    assert_eq!(output.matches("export class TupleU64_U8 extends").count(), 1);
+    assert!(output.contains("  pair: TupleU64_U8 | undefined;\n"));
+    assert!(output.contains("\t\t\t['flat', TupleU64_U8],\n"));
+    assert!(output.contains("\t\t\t['unnamed_0', 'u64'],\n\t\t\t['unnamed_1', 'u8'],\n"));
+
+    // generic tuples have no schema without monomorphization
+    let options = GeneratorOptions {
+        generics: GenericsMode::Generic,
+        ..GeneratorOptions::default()
+    };
+    let err = generate_output_with_options(&layouts[..1], directory, &options).unwrap_err();
+    assert!(err.to_string().contains("`Pair.pair`"));
+}
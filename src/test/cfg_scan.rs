@@ -0,0 +1,114 @@
+use super::write_files;
+use crate::layout::BorshType;
+use crate::{generate_crate_layouts, generate_crate_layouts_with_cfg, CfgSet};
+
+#[test]
+fn generate_layouts_with_active_features() {
+    let root = write_files(
+        "cfg_scan",
+        &[
+            (
+                "src/lib.rs",
+                r#"#[cfg(feature = "admin")]
+                mod admin;
+                #[cfg(test)]
+                mod missing;
+
+                #[cfg(feature = "large")]
+                const CAPACITY: usize = 64;
+                #[cfg(not(feature = "large"))]
+                const CAPACITY: usize = 8;
+
+                #[cfg_attr(feature = "client", derive(BorshSchemaTS))]
+                pub struct Client { id: u8 }
+
+                #[derive(BorshSchemaTS)]
+                pub struct Config {
+                    slots: [u8; CAPACITY],
+                    #[cfg(feature = "admin")]
+                    admin: admin::Admin,
+                    #[cfg_attr(not(feature = "client"), borsh_skip)]
+                    client: Option<u64>,
+                }
+
+                #[derive(BorshSchemaTS)]
+                pub enum Instruction {
+                    Init,
+                    #[cfg(any(feature = "admin", test))]
+                    Close,
+                    Update(#[cfg(feature = "large")] u64, u8),
+                }"#,
+            ),
+            (
+                "src/admin.rs",
+                "#[derive(BorshSchemaTS)]
+                pub struct Admin { key: [u8; 32] }",
+            ),
+        ],
+    );
+
+    let cfg = CfgSet::new();
+    let layouts = generate_crate_layouts_with_cfg(&root, &cfg).unwrap();
+    let names = layouts
+        .iter()
+        .map(|layout| layout.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "Config",
+            "Instruction",
+            "InstructionInit",
+            "InstructionUpdate"
+        ]
+    );
+    let config = &layouts[0];
+    assert_eq!(config.fields.len(), 2);
+    assert_eq!(config.fields[0].ty(), &BorshType::FixedBytes(8));
+    assert!(config.fields[1].should_skip());
+    let update = &layouts[3];
+    assert_eq!(update.fields.len(), 1);
+    assert_eq!(update.fields[0].ty(), &BorshType::U8);
+
+    let cfg = cfg
+        .with_feature("admin")
+        .with_feature("large")
+        .with_feature("client");
+    let layouts = generate_crate_layouts_with_cfg(&root, &cfg).unwrap();
+    let names = layouts
+        .iter()
+        .map(|layout| layout.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "Client",
+            "Config",
+            "Instruction",
+            "InstructionInit",
+            "InstructionClose",
+            "InstructionUpdate",
+            "Admin",
+        ]
+    );
+    let config = &layouts[1];
+    assert_eq!(config.fields[0].ty(), &BorshType::FixedBytes(64));
+    assert_eq!(
+        config.fields[1].ty(),
+        &BorshType::Custom("Admin".to_owned())
+    );
+    assert_eq!(
+        config.fields[2].ty(),
+        &BorshType::Option(Box::new(BorshType::U64))
+    );
+    assert_eq!(layouts[5].fields.len(), 2);
+
+    // `cfg(test)` enables a module without a file
+    let err = generate_crate_layouts_with_cfg(&root, &cfg.with_flag("test"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("file not found for module `missing`"));
+
+    // without a configuration every module is parsed
+    assert!(generate_crate_layouts(&root).is_err());
+}
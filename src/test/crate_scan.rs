@@ -0,0 +1,99 @@
+use super::write_files;
+use crate::generate_crate_layouts;
+use crate::layout::BorshType;
+
+#[test]
+fn generate_layouts_from_module_tree() {
+    let root = write_files(
+        "module_tree",
+        &[
+            (
+                "src/lib.rs",
+                r#"pub mod state;
+                #[path = "ix/instructions.rs"]
+                pub mod instruction;
+                mod processor;"#,
+            ),
+            (
+                "src/state.rs",
+                "mod account;
+                #[derive(BorshSchemaTS)]
+                pub struct Config { fee: u16 }",
+            ),
+            (
+                "src/state/account.rs",
+                "use super::Config;
+                #[derive(BorshSchemaTS)]
+                pub struct Account { config: Config, settings: crate::instruction::Config }",
+            ),
+            (
+                "src/ix/instructions.rs",
+                "mod args;
+                #[derive(BorshSchemaTS)]
+                pub struct Config { admin: [u8; 32] }",
+            ),
+            (
+                "src/ix/args/mod.rs",
+                "#[derive(BorshSchemaTS)]
+                pub struct Args { config: super::Config }",
+            ),
+            (
+                "src/processor.rs",
+                "use crate::state::Config as StateConfig;
+                use crate::instruction::*;
+                #[derive(BorshSchemaTS)]
+                struct Context { state: StateConfig, ix: Config, args: Option<args::Args> }",
+            ),
+            // not part of the module tree
+            (
+                "src/dead.rs",
+                "#[derive(BorshSchemaTS)] struct Dead { x: u8 }",
+            ),
+            (
+                "tests/integration.rs",
+                "#[derive(BorshSchemaTS)] struct Integration { x: u8 }",
+            ),
+        ],
+    );
+
+    let layouts = generate_crate_layouts(&root).unwrap();
+    let names = layouts
+        .iter()
+        .map(|layout| (layout.module_path.join("::"), layout.name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("state".to_owned(), "StateConfig"),
+            ("state::account".to_owned(), "Account"),
+            ("instruction".to_owned(), "InstructionConfig"),
+            ("instruction::args".to_owned(), "Args"),
+            ("processor".to_owned(), "Context"),
+        ]
+    );
+    let state_config = BorshType::Custom("StateConfig".to_owned());
+    let instruction_config = BorshType::Custom("InstructionConfig".to_owned());
+    assert_eq!(layouts[1].fields[0].ty(), &state_config);
+    assert_eq!(layouts[1].fields[1].ty(), &instruction_config);
+    assert_eq!(layouts[3].fields[0].ty(), &instruction_config);
+    assert_eq!(layouts[4].fields[0].ty(), &state_config);
+    assert_eq!(layouts[4].fields[1].ty(), &instruction_config);
+    assert_eq!(
+        layouts[4].fields[2].ty(),
+        &BorshType::Option(Box::new(BorshType::Custom("Args".to_owned())))
+    );
+
+    // the entry file can be passed directly as well
+    let from_entry = generate_crate_layouts(root.join("src/lib.rs")).unwrap();
+    assert_eq!(from_entry.len(), layouts.len());
+}
+
+#[test]
+fn missing_module_file() {
+    let root = write_files("missing_module", &[("src/main.rs", "mod missing;")]);
+    let err = generate_crate_layouts(&root).unwrap_err().to_string();
+    assert!(err.starts_with("file not found for module `missing`"));
+
+    let root = write_files("no_entry", &[("src/other.rs", "")]);
+    assert!(generate_crate_layouts(&root).is_err());
+}
@@ -0,0 +1,142 @@
+//! A minimal decoder that interprets the generated layouts the same way
+//! `borsh-js` interprets the generated schema. It is used to check that the
+//! layouts are able to decode data serialized by `borsh-rs`.
+use crate::layout::{BorshType, Kind, Layout};
+
+use std::convert::TryInto;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Unsigned(u128),
+    Signed(i128),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    Bytes(Vec<u8>),
+    Seq(Vec<Value>),
+    Option(Option<Box<Value>>),
+    Map(Vec<(Value, Value)>),
+    Struct(Vec<(String, Value)>),
+    Enum(String, Box<Value>),
+}
+
+pub struct Decoder<'a> {
+    layouts: &'a [Layout],
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(layouts: &'a [Layout], data: &'a [u8]) -> Self {
+        Self { layouts, data }
+    }
+
+    /// Decodes a value of the given layout and checks that every byte has
+    /// been consumed.
+    pub fn decode_layout(mut self, name: &str) -> Value {
+        let value = self.decode(&BorshType::Custom(name.to_owned()));
+        assert!(self.data.is_empty(), "{} bytes left", self.data.len());
+        value
+    }
+
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        head
+    }
+
+    fn read_u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take(4).try_into().unwrap())
+    }
+
+    fn decode(&mut self, ty: &BorshType) -> Value {
+        match ty {
+            BorshType::U8 => Value::Unsigned(self.take(1)[0] as u128),
+            BorshType::U16 => {
+                Value::Unsigned(u16::from_le_bytes(self.take(2).try_into().unwrap()) as u128)
+            }
+            BorshType::U32 => Value::Unsigned(self.read_u32() as u128),
+            BorshType::U64 => {
+                Value::Unsigned(u64::from_le_bytes(self.take(8).try_into().unwrap()) as u128)
+            }
+            BorshType::U128 => {
+                Value::Unsigned(u128::from_le_bytes(self.take(16).try_into().unwrap()))
+            }
+            BorshType::I8 => Value::Signed(self.take(1)[0] as i8 as i128),
+            BorshType::I16 => {
+                Value::Signed(i16::from_le_bytes(self.take(2).try_into().unwrap()) as i128)
+            }
+            BorshType::I32 => {
+                Value::Signed(i32::from_le_bytes(self.take(4).try_into().unwrap()) as i128)
+            }
+            BorshType::I64 => {
+                Value::Signed(i64::from_le_bytes(self.take(8).try_into().unwrap()) as i128)
+            }
+            BorshType::I128 => {
+                Value::Signed(i128::from_le_bytes(self.take(16).try_into().unwrap()))
+            }
+            BorshType::F32 => {
+                Value::Float(f32::from_le_bytes(self.take(4).try_into().unwrap()) as f64)
+            }
+            BorshType::F64 => Value::Float(f64::from_le_bytes(self.take(8).try_into().unwrap())),
+            BorshType::Bool => Value::Bool(self.take(1)[0] != 0),
+            BorshType::String => {
+                let len = self.read_u32() as usize;
+                Value::String(String::from_utf8(self.take(len).to_vec()).unwrap())
+            }
+            BorshType::Pubkey => Value::Bytes(self.take(32).to_vec()),
+            BorshType::FixedBytes(len) => Value::Bytes(self.take(*len).to_vec()),
+            BorshType::FixedArray(inner, len) => {
+                Value::Seq((0..*len).map(|_| self.decode(inner)).collect())
+            }
+            BorshType::Vec(inner) | BorshType::Set(inner) => {
+                let len = self.read_u32();
+                Value::Seq((0..len).map(|_| self.decode(inner)).collect())
+            }
+            BorshType::Option(inner) => match self.take(1)[0] {
+                0 => Value::Option(None),
+                _ => Value::Option(Some(Box::new(self.decode(inner)))),
+            },
+            BorshType::Map(key, value) => {
+                let len = self.read_u32();
+                Value::Map(
+                    (0..len)
+                        .map(|_| (self.decode(key), self.decode(value)))
+                        .collect(),
+                )
+            }
+            BorshType::Custom(name) => {
+                let layouts = self.layouts;
+                let layout = layouts
+                    .iter()
+                    .find(|layout| &layout.name == name)
+                    .unwrap_or_else(|| panic!("unknown layout {}", name));
+                let mut fields = layout.fields.iter().filter(|field| !field.should_skip());
+                match layout.kind {
+                    Kind::Struct => Value::Struct(
+                        fields
+                            .map(|field| (field.name().to_owned(), self.decode(field.ty())))
+                            .collect(),
+                    ),
+                    Kind::Enum => {
+                        let discriminant = self.take(1)[0];
+                        let index = layout
+                            .discriminants
+                            .iter()
+                            .position(|d| *d == discriminant)
+                            .expect("invalid discriminant");
+                        let variant = fields.nth(index).expect("invalid variant index");
+                        Value::Enum(
+                            variant.name().to_owned(),
+                            Box::new(self.decode(variant.ty())),
+                        )
+                    }
+                }
+            }
+            BorshType::Generic(..) | BorshType::TypeParam(_) => {
+                unreachable!("generic layouts are decoded after monomorphization")
+            }
+            BorshType::Tuple(_) => unreachable!("tuples are decoded after lifting"),
+            BorshType::Skip => unreachable!("skipped fields are not decoded"),
+        }
+    }
+}
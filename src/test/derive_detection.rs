@@ -0,0 +1,82 @@
+use super::layouts_from_source;
+
+fn layout_names(code: &str) -> Vec<String> {
+    layouts_from_source(code)
+        .unwrap()
+        .into_iter()
+        .map(|layout| layout.name)
+        .collect()
+}
+
+#[test]
+fn plain_derive() {
+    let names = layout_names(
+        "#[derive(Clone, BorshSchemaTS, Debug)]
+        struct Plain { field: u8 }
+
+        #[derive(BorshSchemaTS)]
+        enum PlainEnum { A, B(u8) }",
+    );
+    assert_eq!(
+        names,
+        vec!["Plain", "PlainEnum", "PlainEnumA", "PlainEnumB"]
+    );
+}
+
+#[test]
+fn qualified_derive() {
+    let names = layout_names(
+        "#[derive(agsol_borsh_schema::BorshSchemaTS)]
+        struct Qualified { field: u8 }
+
+        #[derive(::agsol_borsh_schema::BorshSchemaTS)]
+        struct Absolute { field: u8 }",
+    );
+    assert_eq!(names, vec!["Qualified", "Absolute"]);
+}
+
+#[test]
+fn cfg_attr_derive() {
+    let names = layout_names(
+        r#"#[cfg_attr(feature = "ts", derive(BorshSchemaTS))]
+        struct Conditional { field: u8 }
+
+        #[cfg_attr(all(feature = "a", not(test)), cfg_attr(feature = "b", derive(Debug, BorshSchemaTS)))]
+        struct Nested { field: u8 }
+
+        #[cfg_attr(feature = "ts", doc = "BorshSchemaTS")]
+        struct NotDerived { field: u8 }"#,
+    );
+    assert_eq!(names, vec!["Conditional", "Nested"]);
+}
+
+#[test]
+fn mentions_are_ignored() {
+    let names = layout_names(
+        r#"/// Doesn't derive BorshSchemaTS.
+        #[derive(Debug)]
+        struct Documented { field: u8 }
+
+        #[derive(BorshSchemaTSExtra)]
+        struct SimilarName { field: u8 }
+
+        #[serde(rename = "BorshSchemaTS")]
+        enum Renamed { A }"#,
+    );
+    assert!(names.is_empty());
+}
+
+#[test]
+fn layouts_are_not_duplicated() {
+    let names = layout_names(
+        r#"/// Derives BorshSchemaTS.
+        #[derive(BorshSchemaTS)]
+        #[cfg_attr(feature = "ts", derive(agsol_borsh_schema::BorshSchemaTS))]
+        struct Twice { field: u8 }
+
+        #[derive(BorshSchemaTS)]
+        #[doc = "BorshSchemaTS"]
+        enum TwiceEnum { A }"#,
+    );
+    assert_eq!(names, vec!["Twice", "TwiceEnum", "TwiceEnumA"]);
+}
@@ -0,0 +1,111 @@
+use super::write_files;
+use crate::{generate_layouts, Diagnostics, Location};
+
+#[test]
+fn collect_located_diagnostics() {
+    let root = write_files(
+        "diagnostics",
+        &[
+            (
+                "state.rs",
+                "#[derive(BorshSchemaTS)]
+struct State {
+    map: BTreeMap<u8>,
+    ok: u64,
+    #[alias(Vec<)]
+    alias: Foo,
+}
+
+#[derive(BorshSchemaTS)]
+enum Event {
+    Created([u8; UNKNOWN]),
+}",
+            ),
+            ("broken.rs", "struct Broken {"),
+        ],
+    );
+    let err = generate_layouts(&root).unwrap_err();
+    let mut diagnostics = err.downcast_ref::<Diagnostics>().unwrap().0.clone();
+    diagnostics.sort_by_key(|diagnostic| diagnostic.path.clone());
+    assert_eq!(diagnostics.len(), 4);
+
+    assert_eq!(diagnostics[0].path, Some(root.join("broken.rs")));
+    assert!(diagnostics[0].item.is_none());
+
+    let state = Some(root.join("state.rs"));
+    let located = diagnostics[1..]
+        .iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.path, state);
+            (
+                diagnostic.item.as_deref().unwrap(),
+                diagnostic.field.as_deref().unwrap(),
+                diagnostic.location.clone().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        located,
+        vec![
+            (
+                "State",
+                "map",
+                Location {
+                    line: 3,
+                    column: 10,
+                    len: 12
+                }
+            ),
+            (
+                "State",
+                "alias",
+                Location {
+                    line: 5,
+                    column: 17,
+                    len: 1
+                }
+            ),
+            (
+                "EventCreated",
+                "unnamed_0",
+                Location {
+                    line: 11,
+                    column: 13,
+                    len: 13
+                }
+            ),
+        ]
+    );
+
+    let rendered = diagnostics[1].to_string();
+    assert!(rendered.contains(&format!("--> {}:3:10", root.join("state.rs").display())));
+    assert!(rendered.contains("3 |     map: BTreeMap<u8>,\n  |          ^^^^^^^^^^^^"));
+    assert!(rendered.ends_with("= note: in field `map` of `State`"));
+    assert!(err
+        .to_string()
+        .ends_with("error: aborting due to 4 previous errors"));
+}
+
+#[test]
+fn diagnostics_include_the_causes() {
+    let root = write_files(
+        "diagnostic_causes",
+        &[(
+            "consts.rs",
+            "const MISSING: usize = UNKNOWN * 2;
+
+#[derive(BorshSchemaTS)]
+struct Buffer {
+    data: [u8; MISSING],
+}",
+        )],
+    );
+    let err = generate_layouts(&root).unwrap_err();
+    let diagnostics = &err.downcast_ref::<Diagnostics>().unwrap().0;
+    assert_eq!(diagnostics.len(), 1);
+    let rendered = diagnostics[0].to_string();
+    assert!(rendered.starts_with(
+        "error: in constant `MISSING`: unresolved constant `UNKNOWN` in array length"
+    ));
+    assert!(rendered.ends_with("= note: in field `data` of `Buffer`"));
+}
@@ -0,0 +1,104 @@
+use super::layouts_from_source;
+use crate::{
+    generate_output, generate_output_with_options, FieldNaming, GeneratorOptions, GenericsMode,
+    Nullability, OutputFiles,
+};
+
+use std::fs;
+use std::path::Path;
+
+const OUTPUT_DIRECTORY: &str = "test-output/generator_options";
+
+const SOURCE: &str = "#[derive(BorshSchemaTS)]
+struct Price { last_value: f64, owner_key: Pubkey }
+#[derive(BorshSchemaTS)]
+enum Update { SetPrice(Price), Clear }
+#[derive(BorshSchemaTS)]
+struct Wrapper<T> { inner_value: T }";
+
+#[test]
+fn default_options() {
+    let layouts = layouts_from_source(SOURCE).unwrap();
+    let directory = Path::new(OUTPUT_DIRECTORY).join("default");
+    generate_output(&layouts, directory.join("plain")).unwrap();
+    generate_output_with_options(
+        &layouts,
+        directory.join("options"),
+        &GeneratorOptions::new(),
+    )
+    .unwrap();
+    let plain = fs::read_to_string(directory.join("plain/schema.ts")).unwrap();
+    let options = fs::read_to_string(directory.join("options/schema.ts")).unwrap();
+    assert_eq!(plain, options);
+    assert!(plain.starts_with("import {Struct, Enum, PublicKeyBE} from 'ts-borsh-schema';\n"));
+    assert!(plain.contains("  lastValue: number | undefined;\n"));
+    assert!(plain.contains("\n  constructor(properties: any) {\n    super(properties);\n"));
+    assert!(plain.contains("export const SCHEMA = new Map<any, any>([\n"));
+}
+
+#[test]
+fn custom_options() {
+    let layouts = layouts_from_source(SOURCE).unwrap();
+    let directory = Path::new(OUTPUT_DIRECTORY).join("custom");
+    let options = GeneratorOptions::new()
+        .with_file_name("layouts.ts")
+        .with_library("@my-org/borsh")
+        .with_schema_name("LAYOUTS")
+        .with_field_naming(FieldNaming::SnakeCase)
+        .with_nullability(Nullability::Optional)
+        .with_indent("    ");
+    generate_output_with_options(&layouts, &directory, &options).unwrap();
+    assert!(!directory.join("schema.ts").exists());
+
+    let output = fs::read_to_string(directory.join("layouts.ts")).unwrap();
+    assert!(output.starts_with("import {Struct, Enum, PublicKeyBE} from '@my-org/borsh';\n"));
+    assert!(output.contains(
+        "export class Price extends Struct {\n    \
+        last_value?: number;\n    \
+        owner_key?: PublicKeyBE;\n\n    \
+        constructor(properties: any) {\n        \
+        super(properties);\n        \
+        rejectNaN(this.last_value, 'last_value');\n    \
+        }\n};\n"
+    ));
+    assert!(output
+        .contains("\n    if (typeof value === 'number' && Number.isNaN(value)) {\n        throw"));
+    assert!(output.contains("['last_value', 'f64'],"));
+    assert!(output.contains("['update_set_price', UpdateSetPrice],"));
+    assert!(output.contains("export const LAYOUTS = new Map<any, any>([\n"));
+    assert!(!output.contains("SCHEMA"));
+
+    let options = options
+        .with_field_naming(FieldNaming::Preserve)
+        .with_nullability(Nullability::Definite)
+        .with_generics(GenericsMode::Generic)
+        .with_files(OutputFiles::Modules);
+    generate_output_with_options(&layouts, &directory, &options).unwrap();
+    let output = fs::read_to_string(directory.join("layouts.ts")).unwrap();
+    assert!(output.contains("    last_value!: number;\n"));
+    assert!(output.contains(
+        "export const wrapperSchema = (T: any): any => ({\n    \
+        kind: 'struct', fields: [\n        \
+        ['inner_value', T],\n    \
+        ],\n});\n"
+    ));
+    let index = fs::read_to_string(directory.join("index.ts")).unwrap();
+    assert!(index.starts_with("import {PublicKeyBE} from '@my-org/borsh';\n"));
+    assert!(index.contains("['UpdateSetPrice', UpdateSetPrice],"));
+    assert!(index.contains(
+        "export const LAYOUTS = new Map<any, any>([\n    \
+        [\n        \
+        Price,\n        \
+        {\n            \
+        kind: 'struct', fields: [\n                \
+        ['last_value', 'f64'],\n                \
+        ['owner_key', PublicKeyBE],\n            \
+        ],\n        \
+        },\n    \
+        ],\n"
+    ));
+    assert!(
+        index.contains("\n    [\n        PublicKeyBE,\n        {\n            kind: 'struct',\n")
+    );
+    assert!(!index.contains('\t'));
+}
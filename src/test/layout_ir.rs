@@ -0,0 +1,97 @@
+use super::layouts_from_source;
+use crate::{BorshType, Kind, Location};
+
+const SOURCE: &str = "/// The state of a pool.
+///
+/// Created by `Initialize`.
+#[derive(BorshSchemaTS)]
+pub struct Pool<T> {
+    /// Owner of the pool.
+    owner: Pubkey,
+    balance: T,
+}
+
+#[derive(BorshSchemaTS)]
+pub enum Instruction {
+    /// Creates a new pool.
+    Initialize { amount: u64 },
+    #[doc = \" Closes the pool.\"]
+    Close,
+}
+";
+
+#[test]
+fn layout_metadata() {
+    let layouts = layouts_from_source(SOURCE).unwrap();
+    let names = layouts
+        .iter()
+        .map(|layout| layout.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "Pool",
+            "Instruction",
+            "InstructionInitialize",
+            "InstructionClose"
+        ]
+    );
+
+    let pool = &layouts[0];
+    assert_eq!(pool.kind, Kind::Struct);
+    assert_eq!(pool.generics, ["T"]);
+    assert_eq!(
+        pool.docs,
+        ["The state of a pool.", "", "Created by `Initialize`."]
+    );
+    assert_eq!(
+        pool.location,
+        Some(Location {
+            line: 5,
+            column: 12,
+            len: 4
+        })
+    );
+    assert_eq!(pool.fields[0].docs, ["Owner of the pool."]);
+    assert!(pool.fields[1].docs.is_empty());
+    assert_eq!(pool.fields[1].ty, BorshType::TypeParam("T".to_owned()));
+
+    let instruction = &layouts[1];
+    assert_eq!(instruction.kind, Kind::Enum);
+    assert_eq!(instruction.discriminants, [0, 1]);
+    assert_eq!(instruction.fields[0].docs, ["Creates a new pool."]);
+    assert_eq!(instruction.fields[1].docs, ["Closes the pool."]);
+    assert_eq!(layouts[2].docs, ["Creates a new pool."]);
+    assert_eq!(layouts[2].location.as_ref().unwrap().line, 14);
+    assert_eq!(layouts[3].location.as_ref().unwrap().line, 16);
+}
+
+#[test]
+fn transformed_layout_emission() {
+    let mut layouts = layouts_from_source(SOURCE).unwrap();
+    let pool = &mut layouts[0];
+    pool.name = "LiquidityPool".to_owned();
+    pool.fields.retain(|field| field.name != "balance");
+    pool.generics.clear();
+    assert_eq!(
+        pool.to_ts_class().unwrap(),
+        "export class LiquidityPool extends Struct {\n  owner: PublicKeyBE | undefined;\n};\n\n"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    use crate::Layout;
+
+    let layouts = layouts_from_source(SOURCE).unwrap();
+    let json = serde_json::to_string(&layouts).unwrap();
+    let decoded = serde_json::from_str::<Vec<Layout>>(&json).unwrap();
+    assert_eq!(format!("{:?}", decoded), format!("{:?}", layouts));
+    for (decoded, layout) in decoded.iter().zip(&layouts) {
+        assert_eq!(
+            decoded.to_ts_class().unwrap(),
+            layout.to_ts_class().unwrap()
+        );
+    }
+}
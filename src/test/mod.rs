@@ -1,6 +1,62 @@
+mod borsh_aliases;
+mod borsh_attributes;
 mod borsh_btree;
+mod borsh_consts;
+mod borsh_discriminants;
 mod borsh_enums;
+mod borsh_floats;
+mod borsh_generics;
+mod borsh_modules;
+mod borsh_pointers;
+mod borsh_signed;
 mod borsh_structs;
+mod borsh_tuples;
+mod cfg_scan;
+mod crate_scan;
+mod decode;
+mod derive_detection;
+mod diagnostics;
 mod generate_output;
+mod generator_options;
+mod layout_ir;
+mod no_panic;
+mod output_order;
+mod pubkey_strategy;
+mod runtime_module;
+mod split_output;
+mod validation;
+mod workspace_scan;
+
+use crate::layout::{CrateScope, Layout};
+use crate::utils::{generate_layouts_from_files, SourceFile};
+
+use std::fs;
+use std::path::{Path, PathBuf};
 
 const TEST_DATA_DIRECTORY: &str = "test-data";
+
+/// Generates the layouts of the data structures declared in a source string.
+fn layouts_from_source(code: &str) -> Result<Vec<Layout>, anyhow::Error> {
+    let file = SourceFile {
+        path: None,
+        code: code.to_owned(),
+        module_path: Vec::new(),
+        crate_scope: CrateScope::default(),
+        syntax: syn::parse_file(code)?,
+    };
+    generate_layouts_from_files(&[file], Vec::new())
+}
+
+/// Writes files into a fresh directory in the test data directory.
+fn write_files(directory: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = Path::new(TEST_DATA_DIRECTORY).join(directory);
+    if root.exists() {
+        fs::remove_dir_all(&root).unwrap();
+    }
+    for (path, code) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, code).unwrap();
+    }
+    root
+}
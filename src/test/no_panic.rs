@@ -0,0 +1,269 @@
+use super::layouts_from_source;
+use crate::layout::{monomorphize, validate, BorshType, EmitError};
+use crate::{generate_output_with_options, GeneratorOptions, GenericsMode};
+
+use std::panic::{self, AssertUnwindSafe};
+
+/// A small xorshift generator, so that failures can be reproduced from the
+/// seed without depending on a fuzzing crate.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+const LEAVES: &[&str] = &[
+    "u8",
+    "i8",
+    "u16",
+    "i64",
+    "u128",
+    "f32",
+    "f64",
+    "bool",
+    "String",
+    "str",
+    "Pubkey",
+    "PublicKey",
+    "T",
+    "U",
+    "N",
+    "Self",
+    "_",
+    "!",
+    "()",
+    "Alias",
+    "Pair",
+    "Known",
+    "Unknown",
+    "Wrapper",
+    "crate::Known",
+    "super::Known",
+    "self::Alias",
+    "std::marker::PhantomData<u8>",
+    "PhantomData",
+    "Vec",
+    "Option",
+    "BTreeMap",
+    "HashSet",
+    "Box",
+    "Cow",
+    "impl Trait",
+    "dyn Trait",
+    "fn(u8) -> u8",
+    "<T as Trait>::Output",
+];
+
+const WRAPPERS: &[&str] = &[
+    "Vec",
+    "VecDeque",
+    "Option",
+    "Box",
+    "Rc",
+    "Arc",
+    "HashSet",
+    "BTreeSet",
+    "PhantomData",
+    "Pair",
+    "Wrapper",
+    "Known",
+    "Alias",
+    "std::vec::Vec",
+    "Cow<'a,",
+];
+
+const MAPS: &[&str] = &["HashMap", "BTreeMap", "Wrapper", "Pair", "Option", "Vec"];
+
+const LENGTHS: &[&str] = &[
+    "0",
+    "1",
+    "32",
+    "255",
+    "256",
+    "N",
+    "LEN",
+    "LEN * 2",
+    "LEN - 100",
+    "LEN / 0",
+    "LEN % 0",
+    "1 << 70",
+    "u64::MAX",
+    "-1",
+    "usize::MAX + 1",
+    "size_of::<u64>()",
+    "size_of::<T>()",
+    "LOOP",
+    "MISSING",
+    "(LEN + 1) as usize",
+    "!0",
+    "\"str\"",
+    "LEN as u8",
+    "18446744073709551615 * 2",
+];
+
+fn random_type(rng: &mut Rng, depth: usize) -> String {
+    if depth == 0 {
+        return rng.pick(LEAVES).to_owned();
+    }
+    match rng.below(9) {
+        0 | 1 => rng.pick(LEAVES).to_owned(),
+        2 => {
+            let wrapper = rng.pick(WRAPPERS);
+            let inner = random_type(rng, depth - 1);
+            if wrapper.ends_with(',') {
+                format!("{} {}>", wrapper, inner)
+            } else {
+                format!("{}<{}>", wrapper, inner)
+            }
+        }
+        3 => format!(
+            "{}<{}, {}>",
+            rng.pick(MAPS),
+            random_type(rng, depth - 1),
+            random_type(rng, depth - 1)
+        ),
+        4 => format!("[{}; {}]", random_type(rng, depth - 1), rng.pick(LENGTHS)),
+        5 => {
+            let elems = (0..rng.below(4))
+                .map(|_| random_type(rng, depth - 1))
+                .collect::<Vec<_>>();
+            if elems.len() == 1 {
+                format!("({},)", elems[0])
+            } else {
+                format!("({})", elems.join(", "))
+            }
+        }
+        6 => format!("&'a {}", random_type(rng, depth - 1)),
+        7 => format!("[{}]", random_type(rng, depth - 1)),
+        _ => format!(
+            "Wrapper<{}, {}, {}>",
+            random_type(rng, depth - 1),
+            random_type(rng, depth - 1),
+            random_type(rng, depth - 1)
+        ),
+    }
+}
+
+/// Runs every stage of the generator on a source, ignoring the errors.
+fn run_pipeline(code: &str, output_directory: &str) {
+    let layouts = match layouts_from_source(code) {
+        Ok(layouts) => layouts,
+        Err(_) => return,
+    };
+    let _ = validate(&layouts);
+    for layout in &layouts {
+        let _ = layout.to_ts_class();
+        let _ = layout.to_borsh_schema();
+        let _ = layout.to_schema_factory();
+    }
+    let _ = monomorphize(&layouts);
+    for generics in [GenericsMode::Monomorphize, GenericsMode::Generic] {
+        let options = GeneratorOptions {
+            generics,
+            ..GeneratorOptions::default()
+        };
+        let _ = generate_output_with_options(&layouts, output_directory, &options);
+    }
+}
+
+#[test]
+fn arbitrary_types_never_panic() {
+    let output_directory = "test-data/no-panic-output";
+    let mut rng = Rng(0x5eed_1234_abcd_ef01);
+    let mut failures = Vec::new();
+    for _ in 0..1500 {
+        let field = random_type(&mut rng, 4);
+        if syn::parse_str::<syn::Type>(&field).is_err() {
+            continue;
+        }
+        let prelude = "const LEN: usize = 64;
+            const LOOP: usize = LOOP + 1;
+            type Alias = [u8; LEN];
+            type Pair<T> = (T, T);
+            #[derive(BorshSchemaTS)]
+            struct Known { x: u8 }
+            #[derive(BorshSchemaTS)]
+            struct Wrapper<T> { inner: Vec<T>, other: Option<T> }";
+        let sources = [
+            format!(
+                "{}
+                #[derive(BorshSchemaTS)]
+                struct Fuzzed<T> {{ field: {}, other: T }}
+                #[derive(BorshSchemaTS)]
+                struct Instance {{ fuzzed: Fuzzed<u64> }}",
+                prelude, field
+            ),
+            format!(
+                "{}
+                #[derive(BorshSchemaTS)]
+                #[borsh(use_discriminant = true)]
+                enum FuzzedEnum {{ A({}) = 3, B {{ b: Option<Known> }} }}",
+                prelude, field
+            ),
+        ];
+        let ty = syn::parse_str::<syn::Type>(&field).unwrap();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _ = BorshType::try_from(&ty);
+            for code in &sources {
+                run_pipeline(code, output_directory);
+            }
+        }));
+        if result.is_err() {
+            failures.push(field);
+        }
+    }
+    assert!(failures.is_empty(), "panicked on {:?}", failures);
+}
+
+#[test]
+fn nested_skipped_type_is_an_error() {
+    let ty = BorshType::Option(Box::new(BorshType::Skip));
+    assert_eq!(ty.to_borsh_schema(), Err(EmitError::NestedSkip));
+    assert_eq!(ty.to_class_type(), Err(EmitError::NestedSkip));
+
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        struct Marker { marker: Option<std::marker::PhantomData<u8>> }",
+    )
+    .unwrap();
+    let err = layouts[0].to_ts_class().unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<EmitError>(),
+        Some(&EmitError::NestedSkip)
+    );
+    assert_eq!(err.to_string(), "can't emit field `marker` of `Marker`");
+    assert!(layouts[0].to_borsh_schema().is_err());
+}
+
+#[test]
+fn recursive_instantiation_is_an_error() {
+    for source in [
+        "#[derive(BorshSchemaTS)]
+        struct W<T> { v: T, inner: Option<Box<W<Vec<T>>>> }
+        #[derive(BorshSchemaTS)]
+        struct Root { w: W<u8> }",
+        // every instantiation branches into two new ones
+        "#[derive(BorshSchemaTS)]
+        struct W<T> { a: Option<Box<W<Vec<T>>>>, b: Option<Box<W<Option<T>>>> }
+        #[derive(BorshSchemaTS)]
+        struct Root { w: W<u8> }",
+    ] {
+        let layouts = layouts_from_source(source).unwrap();
+        let err = monomorphize(&layouts).unwrap_err();
+        assert!(err.to_string().contains("`W`"), "{}", err);
+        run_pipeline(source, "test-data/no-panic-output");
+    }
+}
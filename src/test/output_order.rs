@@ -0,0 +1,62 @@
+use super::layouts_from_source;
+use crate::generate_output;
+use crate::layout::Layout;
+
+use std::fs;
+use std::path::Path;
+
+const OUTPUT_DIRECTORY: &str = "test-output/output_order";
+
+fn output_of(layouts: &[Layout], name: &str) -> String {
+    let directory = Path::new(OUTPUT_DIRECTORY).join(name);
+    generate_output(layouts, &directory).unwrap();
+    fs::read_to_string(directory.join("schema.ts")).unwrap()
+}
+
+#[test]
+fn output_is_independent_of_layout_order() {
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        struct Account { state: State, history: Vec<Event> }
+        #[derive(BorshSchemaTS)]
+        enum Event { Opened(Wrapper<u8>), Closed }
+        #[derive(BorshSchemaTS)]
+        struct State { owner: Pubkey }
+        #[derive(BorshSchemaTS)]
+        struct Wrapper<T> { inner: T }
+        #[derive(BorshSchemaTS)]
+        struct Tree { root: Option<Box<Node>> }
+        #[derive(BorshSchemaTS)]
+        struct Node { children: Vec<Tree>, parent: Option<Box<Node>> }",
+    )
+    .unwrap();
+
+    let output = output_of(&layouts, "original");
+    let mut reversed = layouts.clone();
+    reversed.reverse();
+    assert_eq!(output_of(&reversed, "reversed"), output);
+    let mut rotated = layouts.clone();
+    rotated.rotate_left(3);
+    assert_eq!(output_of(&rotated, "rotated"), output);
+
+    let classes = output
+        .lines()
+        .filter_map(|line| line.strip_prefix("export class "))
+        .map(|line| line.split(' ').next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        classes,
+        [
+            "State",
+            "WrapperU8",
+            "EventOpened",
+            "EventClosed",
+            "Event",
+            "Account",
+            "Tree",
+            "Node",
+        ]
+    );
+    let schema = output.find("export const SCHEMA").unwrap();
+    assert!(output.rfind("export class").unwrap() < schema);
+}
@@ -0,0 +1,106 @@
+use super::layouts_from_source;
+use crate::{generate_output_with_options, GeneratorOptions, OutputFiles, PubkeyStrategy};
+
+use std::fs;
+use std::path::Path;
+
+const OUTPUT_DIRECTORY: &str = "test-output/pubkey_strategy";
+
+const SOURCE: &str = "#[derive(BorshSchemaTS)]
+struct Vault { owner: Pubkey, delegates: Vec<Option<Pubkey>> }";
+
+fn output_with(pubkey: PubkeyStrategy, name: &str) -> String {
+    let layouts = layouts_from_source(SOURCE).unwrap();
+    let directory = Path::new(OUTPUT_DIRECTORY).join(name);
+    let options = GeneratorOptions::new().with_pubkey(pubkey);
+    generate_output_with_options(&layouts, &directory, &options).unwrap();
+    fs::read_to_string(directory.join("schema.ts")).unwrap()
+}
+
+#[test]
+fn public_key_be() {
+    let output = output_with(PubkeyStrategy::PublicKeyBE, "public_key_be");
+    assert!(output.starts_with("import {Struct, Enum, PublicKeyBE} from 'ts-borsh-schema';\n"));
+    assert!(output.contains("  owner: PublicKeyBE | undefined;\n"));
+    assert!(output.contains("['delegates', [{ kind: 'option', type: PublicKeyBE }]],"));
+    assert!(output.contains("\n  [\n    PublicKeyBE,\n    {\n      kind: 'struct',"));
+    assert!(!output.contains("BinaryReader"));
+}
+
+#[test]
+fn web3_public_key() {
+    let output = output_with(PubkeyStrategy::Web3, "web3");
+    assert!(output.starts_with(
+        "import {Struct, Enum} from 'ts-borsh-schema';\n\
+        import {PublicKey} from '@solana/web3.js';\n\
+        import {BinaryReader, BinaryWriter} from 'borsh';\n\
+        \n\
+        (BinaryReader.prototype as any).readPubkey = function (this: BinaryReader): PublicKey {\n  \
+        return new PublicKey(this.readFixedArray(32));\n\
+        };\n"
+    ));
+    assert!(
+        output.contains(".writePubkey = function (this: BinaryWriter, value: PublicKey): void {\n")
+    );
+    assert!(output.contains("  owner: PublicKey | undefined;\n"));
+    assert!(output.contains("['owner', 'pubkey'],"));
+    assert!(output.contains("['delegates', [{ kind: 'option', type: 'pubkey' }]],"));
+    assert!(!output.contains("PublicKeyBE"));
+    // no schema is needed for `PublicKey`
+    assert!(output.ends_with("            },\n    ],\n]);"));
+}
+
+#[test]
+fn bytes_and_base58() {
+    let output = output_with(PubkeyStrategy::Bytes, "bytes");
+    assert!(output.starts_with(
+        "import {Struct, Enum} from 'ts-borsh-schema';\nexport class Vault extends Struct {\n"
+    ));
+    assert!(output.contains("  owner: Uint8Array | undefined;\n"));
+    assert!(output.contains("['owner', [32]],"));
+    assert!(!output.contains("PublicKey"));
+
+    let output = output_with(PubkeyStrategy::Base58, "base58");
+    assert!(output.starts_with(
+        "import {Struct, Enum} from 'ts-borsh-schema';\n\
+        import {BinaryReader, BinaryWriter} from 'borsh';\n\
+        import bs58 from 'bs58';\n"
+    ));
+    assert!(output.contains("  return bs58.encode(this.readFixedArray(32));\n"));
+    assert!(output.contains("  owner: string | undefined;\n"));
+    assert!(output.contains("['owner', 'base58Pubkey'],"));
+    assert!(!output.contains("PublicKey"));
+}
+
+#[test]
+fn registration_in_index() {
+    let layouts = layouts_from_source(SOURCE).unwrap();
+    let directory = Path::new(OUTPUT_DIRECTORY).join("modules");
+    let options = GeneratorOptions::new()
+        .with_pubkey(PubkeyStrategy::Web3)
+        .with_files(OutputFiles::Modules)
+        .with_indent("    ");
+    generate_output_with_options(&layouts, &directory, &options).unwrap();
+
+    let schema = fs::read_to_string(directory.join("schema.ts")).unwrap();
+    assert!(schema.starts_with(
+        "import {Struct, Enum} from 'ts-borsh-schema';\n\
+        import {PublicKey} from '@solana/web3.js';\n\
+        export class Vault extends Struct {\n"
+    ));
+    assert!(!schema.contains("BinaryReader"));
+
+    let index = fs::read_to_string(directory.join("index.ts")).unwrap();
+    assert!(index.starts_with(
+        "import {PublicKey} from '@solana/web3.js';\n\
+        import {BinaryReader, BinaryWriter} from 'borsh';\n\
+        import {Vault} from './schema';\n\
+        \n\
+        export * from './schema';\n\
+        \n\
+        (BinaryReader.prototype as any).readPubkey"
+    ));
+    assert!(
+        index.contains("\n    this.writeFixedArray(value.toBytes());\n};\n\nexport const SCHEMA")
+    );
+}
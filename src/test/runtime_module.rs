@@ -0,0 +1,89 @@
+use super::layouts_from_source;
+use crate::{generate_output_with_options, GeneratorOptions, OutputFiles, PubkeyStrategy};
+
+use std::fs;
+use std::path::Path;
+
+const OUTPUT_DIRECTORY: &str = "test-output/runtime_module";
+
+const SOURCE: &str = "#[derive(BorshSchemaTS)]
+struct Account { owner: Pubkey, balance: u64 }
+#[derive(BorshSchemaTS)]
+enum Instruction { Deposit(Account), Close }";
+
+#[test]
+fn runtime_next_to_schema() {
+    let layouts = layouts_from_source(SOURCE).unwrap();
+    let directory = Path::new(OUTPUT_DIRECTORY).join("single");
+    let options = GeneratorOptions::new().with_runtime(true);
+    generate_output_with_options(&layouts, &directory, &options).unwrap();
+
+    let runtime = fs::read_to_string(directory.join("runtime.ts")).unwrap();
+    assert!(runtime.starts_with(
+        "import {deserialize as borshDeserialize, serialize as borshSerialize} from 'borsh';\n\
+        import BN from 'bn.js';\n"
+    ));
+    for export in [
+        "export class Struct {",
+        "export class Enum {",
+        "export class PublicKeyBE extends Struct {",
+        "export function serialize(schema: Schema, object: Struct | Enum): Uint8Array {",
+        "export function deserialize<T>(",
+    ] {
+        assert!(runtime.contains(export), "missing `{}`", export);
+    }
+    assert!(!runtime.contains("ts-borsh-schema"));
+    // fields declared by the classes would reset the assigned properties
+    assert!(runtime.contains("  declare value: Uint8Array;\n"));
+    assert!(!runtime.contains("Buffer.from"));
+
+    let schema = fs::read_to_string(directory.join("schema.ts")).unwrap();
+    assert!(schema.starts_with("import {Struct, Enum, PublicKeyBE, BN} from './runtime';\n"));
+    assert!(!schema.contains("ts-borsh-schema"));
+
+    let options = options
+        .with_pubkey(PubkeyStrategy::Bytes)
+        .with_indent("    ");
+    generate_output_with_options(&layouts, &directory, &options).unwrap();
+    let runtime = fs::read_to_string(directory.join("runtime.ts")).unwrap();
+    assert!(runtime.contains("\n    constructor(properties: any) {\n        Object.assign"));
+    let schema = fs::read_to_string(directory.join("schema.ts")).unwrap();
+    assert!(schema.starts_with("import {Struct, Enum, BN} from './runtime';\n"));
+}
+
+#[test]
+fn runtime_imports_from_nested_files() {
+    let layouts = layouts_from_source(SOURCE).unwrap();
+    let directory = Path::new(OUTPUT_DIRECTORY).join("layouts");
+    let options = GeneratorOptions::new()
+        .with_runtime(true)
+        .with_files(OutputFiles::Layouts);
+    generate_output_with_options(&layouts, &directory, &options).unwrap();
+
+    assert!(directory.join("runtime.ts").is_file());
+    let account = fs::read_to_string(directory.join("account.ts")).unwrap();
+    assert!(account.starts_with("import {Struct, Enum, PublicKeyBE, BN} from './runtime';\n"));
+    let index = fs::read_to_string(directory.join("index.ts")).unwrap();
+    assert!(index.starts_with("import {PublicKeyBE} from './runtime';\n"));
+}
+
+#[test]
+fn runtime_module_is_reserved() {
+    let layouts = layouts_from_source(SOURCE).unwrap();
+    let directory = Path::new(OUTPUT_DIRECTORY).join("reserved");
+    if directory.exists() {
+        fs::remove_dir_all(&directory).unwrap();
+    }
+    let options = GeneratorOptions::new()
+        .with_runtime(true)
+        .with_file_name("runtime.ts");
+    let error = generate_output_with_options(&layouts, &directory, &options).unwrap_err();
+    assert!(error.to_string().contains("reserved for the runtime"));
+    assert!(!directory.join("runtime.ts").exists());
+
+    // without the runtime the name is free to use
+    let options = options.with_runtime(false);
+    generate_output_with_options(&layouts, &directory, &options).unwrap();
+    let output = fs::read_to_string(directory.join("runtime.ts")).unwrap();
+    assert!(output.starts_with("import {Struct, Enum, PublicKeyBE} from 'ts-borsh-schema';\n"));
+}
@@ -0,0 +1,134 @@
+use super::write_files;
+use crate::{
+    generate_crate_layouts, generate_output, generate_output_with_options, GeneratorOptions,
+    OutputFiles,
+};
+
+use std::fs;
+use std::path::Path;
+
+fn write_crate(directory: &str) -> std::path::PathBuf {
+    write_files(
+        directory,
+        &[
+            (
+                "src/lib.rs",
+                "pub mod instructions;
+                pub mod state;
+                #[derive(BorshSchemaTS)]
+                pub struct Config { admin: Pubkey, fee: state::Fee }",
+            ),
+            (
+                "src/instructions.rs",
+                "#[derive(BorshSchemaTS)]
+                pub enum Instruction { Init(crate::Config), Close }",
+            ),
+            (
+                "src/state.rs",
+                "pub mod accounts;
+                #[derive(BorshSchemaTS)]
+                pub struct Fee { rate: u16, vault: Option<Box<accounts::Vault>> }",
+            ),
+            (
+                "src/state/accounts.rs",
+                "use super::Fee;
+                use crate::Config;
+                #[derive(BorshSchemaTS)]
+                pub struct Vault { fee: Fee, config: Config }",
+            ),
+        ],
+    )
+}
+
+fn read(output: &Path, file: &str) -> String {
+    fs::read_to_string(output.join(file)).unwrap()
+}
+
+fn schema_of(module: &str) -> &str {
+    &module[module.find("export const SCHEMA").unwrap()..]
+}
+
+#[test]
+fn one_file_per_module() {
+    let root = write_crate("split_modules");
+    let layouts = generate_crate_layouts(&root).unwrap();
+    let output = root.join("output");
+    let options = GeneratorOptions {
+        files: OutputFiles::Modules,
+        ..GeneratorOptions::default()
+    };
+    generate_output_with_options(&layouts, &output, &options).unwrap();
+
+    let schema = read(&output, "schema.ts");
+    assert!(schema.contains("import {Fee} from './state';\n"));
+    assert!(schema.contains("export class Config extends Struct {"));
+    assert!(!schema.contains("SCHEMA"));
+
+    let state = read(&output, "state.ts");
+    assert!(state.contains("import {Vault} from './state/accounts';\n"));
+    assert!(state.contains("export class Fee extends Struct {"));
+
+    let accounts = read(&output, "state/accounts.ts");
+    assert!(accounts.contains("import {Config} from '../schema';\nimport {Fee} from '../state';\n"));
+    assert!(accounts.contains("export class Vault extends Struct {"));
+
+    let instructions = read(&output, "instructions.ts");
+    assert!(instructions.contains("import {Config} from './schema';\n"));
+    assert!(instructions.contains("export class InstructionInit extends Struct {"));
+    assert!(instructions.contains("export class Instruction extends Enum {"));
+
+    let index = read(&output, "index.ts");
+    assert!(index.starts_with(
+        "import {PublicKeyBE} from 'ts-borsh-schema';\n\
+        import {Instruction, InstructionClose, InstructionInit} from './instructions';\n\
+        import {Config} from './schema';\n\
+        import {Fee} from './state';\n\
+        import {Vault} from './state/accounts';\n\
+        \n\
+        export * from './instructions';\n\
+        export * from './schema';\n\
+        export * from './state';\n\
+        export * from './state/accounts';\n\
+        \n\
+        export const SCHEMA = new Map<any, any>([\n"
+    ));
+
+    // the combined schema is the same as the schema of the single file output
+    let single = root.join("single");
+    generate_output(&layouts, &single).unwrap();
+    assert_eq!(schema_of(&index), schema_of(&read(&single, "schema.ts")));
+}
+
+#[test]
+fn one_file_per_layout() {
+    let root = write_crate("split_layouts");
+    let layouts = generate_crate_layouts(&root).unwrap();
+    let output = root.join("output");
+    let options = GeneratorOptions {
+        files: OutputFiles::Layouts,
+        ..GeneratorOptions::default()
+    };
+    generate_output_with_options(&layouts, &output, &options).unwrap();
+
+    for file in [
+        "config.ts",
+        "instructions/instruction.ts",
+        "instructions/instruction_init.ts",
+        "instructions/instruction_close.ts",
+        "state/fee.ts",
+        "state/accounts/vault.ts",
+        "index.ts",
+    ] {
+        assert!(output.join(file).is_file(), "missing `{}`", file);
+    }
+    let vault = read(&output, "state/accounts/vault.ts");
+    assert!(vault.contains("import {Config} from '../../config';\nimport {Fee} from '../fee';\n"));
+    let instruction = read(&output, "instructions/instruction.ts");
+    assert!(instruction.contains(
+        "import {InstructionClose} from './instruction_close';\n\
+        import {InstructionInit} from './instruction_init';\n"
+    ));
+    let index = read(&output, "index.ts");
+    assert!(index.contains("import {Vault} from './state/accounts/vault';\n"));
+    assert!(index.contains("export * from './instructions/instruction_init';\n"));
+}
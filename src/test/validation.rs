@@ -0,0 +1,72 @@
+use super::{layouts_from_source, write_files};
+use crate::{generate_crate_layouts, generate_output, validate_layouts};
+
+#[test]
+fn undefined_type_references() {
+    let root = write_files(
+        "validation",
+        &[
+            (
+                "src/lib.rs",
+                "mod state;
+                type Key = [u8; 32];
+                #[derive(BorshSchemaTS)]
+                struct Known { key: Key, state: state::State }",
+            ),
+            (
+                "src/state.rs",
+                "#[derive(BorshSchemaTS)]
+                pub struct State { owner: Account, values: Vec<Option<Value>> }
+                #[derive(BorshSchemaTS)]
+                pub enum Event { Created(Account), Closed }",
+            ),
+        ],
+    );
+    let layouts = generate_crate_layouts(&root).unwrap();
+    let err = validate_layouts(&layouts).unwrap_err().to_string();
+    let state_file = root.join("src/state.rs");
+    assert_eq!(
+        err,
+        format!(
+            "undefined types referenced by layouts:\n  \
+            `Account` used by `state::State.owner` in `{0}`\n  \
+            `Value` used by `state::State.values` in `{0}`\n  \
+            `Account` used by `state::EventCreated.unnamed_0` in `{0}`",
+            state_file.display()
+        )
+    );
+
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        struct Wrapper<T> { inner: T, nested: Vec<Wrapper<u8>> }
+        #[derive(BorshSchemaTS)]
+        struct Holder { wrapper: Wrapper<u64>, bytes: [u8; 32] }",
+    )
+    .unwrap();
+    validate_layouts(&layouts).unwrap();
+
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        struct Holder { wrapper: Wrapper<u64> }",
+    )
+    .unwrap();
+    let err = validate_layouts(&layouts).unwrap_err().to_string();
+    assert!(err.ends_with("`Wrapper` used by `Holder.wrapper` in unknown file"));
+}
+
+#[test]
+fn output_of_undefined_type_references() {
+    let layouts = layouts_from_source(
+        "#[derive(BorshSchemaTS)]
+        struct Holder { account: Account }",
+    )
+    .unwrap();
+    let output = write_files("validation_output", &[]);
+    let err = generate_output(&layouts, &output).unwrap_err().to_string();
+    assert_eq!(
+        err,
+        "undefined types referenced by layouts:\n  \
+        `Account` used by `Holder.account` in unknown file"
+    );
+    assert!(!output.join("schema.ts").exists());
+}
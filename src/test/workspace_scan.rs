@@ -0,0 +1,251 @@
+use super::write_files;
+use crate::layout::BorshType;
+use crate::{
+    generate_output_with_options, generate_workspace_layouts, GeneratorOptions, OutputFiles,
+};
+
+use std::fs;
+use std::path::PathBuf;
+
+fn write_workspace(directory: &str) -> PathBuf {
+    write_files(
+        directory,
+        &[
+            (
+                "Cargo.toml",
+                r#"[workspace]
+                members = ["programs/*", "common"]
+                exclude = ["programs/ignored"]
+
+                [workspace.dependencies]
+                shared = { path = "libs/shared" }"#,
+            ),
+            (
+                "programs/vault/Cargo.toml",
+                r#"[package]
+                name = "vault-program"
+                version = "0.1.0"
+
+                [dependencies]
+                borsh = "0.9"
+                common = { package = "my-common", path = "../../common" }"#,
+            ),
+            (
+                "programs/vault/src/lib.rs",
+                "pub mod state;
+                use common::Config;
+                #[derive(BorshSchemaTS)]
+                pub struct Vault { config: Config, account: state::Account }",
+            ),
+            (
+                "programs/vault/src/state.rs",
+                "#[derive(BorshSchemaTS)]
+                pub struct Account { owner: shared::Owner, config: common::state::Config }",
+            ),
+            (
+                "programs/ignored/Cargo.toml",
+                r#"[package]
+                name = "ignored""#,
+            ),
+            (
+                "programs/ignored/src/lib.rs",
+                "#[derive(BorshSchemaTS)] struct Ignored { x: u8 }",
+            ),
+            (
+                "common/Cargo.toml",
+                r#"[package]
+                name = "my-common"
+
+                [dependencies]
+                shared.workspace = true"#,
+            ),
+            (
+                "common/src/lib.rs",
+                "pub mod state;
+                #[derive(BorshSchemaTS)]
+                pub struct Config { fee: u16, owner: shared::Owner }",
+            ),
+            (
+                "common/src/state.rs",
+                "#[derive(BorshSchemaTS)]
+                pub struct Config { paused: bool }",
+            ),
+            (
+                "libs/shared/Cargo.toml",
+                r#"[package]
+                name = "shared"
+
+                [lib]
+                path = "lib.rs""#,
+            ),
+            (
+                "libs/shared/lib.rs",
+                "#[derive(BorshSchemaTS)]
+                pub struct Owner { key: Pubkey }",
+            ),
+        ],
+    )
+}
+
+#[test]
+fn generate_layouts_from_workspace() {
+    let root = write_workspace("workspace_layouts");
+    let layouts = generate_workspace_layouts(root.join("Cargo.toml")).unwrap();
+    let names = layouts
+        .iter()
+        .map(|layout| {
+            (
+                layout.crate_name.as_deref().unwrap(),
+                layout.module_path.join("::"),
+                layout.name.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("vault_program", "vault_program".to_owned(), "Vault"),
+            (
+                "vault_program",
+                "vault_program::state".to_owned(),
+                "Account"
+            ),
+            ("my_common", "my_common".to_owned(), "MyCommonConfig"),
+            (
+                "my_common",
+                "my_common::state".to_owned(),
+                "MyCommonStateConfig"
+            ),
+            ("shared", "shared".to_owned(), "Owner"),
+        ]
+    );
+
+    let custom = |name: &str| BorshType::Custom(name.to_owned());
+    assert_eq!(layouts[0].fields[0].ty(), &custom("MyCommonConfig"));
+    assert_eq!(layouts[0].fields[1].ty(), &custom("Account"));
+    assert_eq!(layouts[1].fields[0].ty(), &custom("Owner"));
+    assert_eq!(layouts[1].fields[1].ty(), &custom("MyCommonStateConfig"));
+    assert_eq!(layouts[2].fields[1].ty(), &custom("Owner"));
+
+    // the workspace directory works as well
+    assert_eq!(generate_workspace_layouts(&root).unwrap().len(), 5);
+}
+
+#[test]
+fn generate_crate_modules() {
+    let root = write_workspace("workspace_modules");
+    let layouts = generate_workspace_layouts(&root).unwrap();
+    let output = root.join("output");
+    let options = GeneratorOptions {
+        files: OutputFiles::Crates,
+        ..GeneratorOptions::default()
+    };
+    generate_output_with_options(&layouts, &output, &options).unwrap();
+
+    let vault = fs::read_to_string(output.join("vault_program.ts")).unwrap();
+    assert!(vault.contains("import {MyCommonConfig, MyCommonStateConfig} from './my_common';\n"));
+    assert!(vault.contains("import {Owner} from './shared';\n"));
+    assert!(vault.contains("export class Vault extends Struct {"));
+    assert!(!vault.contains("export class Owner"));
+    assert!(!vault.contains("SCHEMA"));
+
+    let common = fs::read_to_string(output.join("my_common.ts")).unwrap();
+    assert!(common.contains("import {Owner} from './shared';\n"));
+    assert!(!common.contains("vault"));
+
+    let shared = fs::read_to_string(output.join("shared.ts")).unwrap();
+    assert!(!shared.contains("import {Owner"));
+    assert!(shared.contains("export class Owner extends Struct {"));
+
+    let index = fs::read_to_string(output.join("index.ts")).unwrap();
+    assert!(index.contains("import {Owner} from './shared';\n"));
+    assert!(index.contains("export * from './vault_program';\n"));
+    assert!(index.contains("export const SCHEMA = new Map<any, any>([\n"));
+    for class in ["Owner", "MyCommonConfig", "Vault"] {
+        assert!(index.contains(&format!("\n            {},\n", class)));
+    }
+    assert!(!output.join("schema.ts").exists());
+}
+
+#[test]
+fn shared_instances_in_crate_modules() {
+    // the program is scanned before the crate it depends on, so it's the
+    // first user of the instantiation and the tuple
+    let root = write_files(
+        "workspace_instances",
+        &[
+            (
+                "Cargo.toml",
+                r#"[workspace]
+                members = ["program", "common"]"#,
+            ),
+            (
+                "program/Cargo.toml",
+                r#"[package]
+                name = "program"
+
+                [dependencies]
+                common = { path = "../common" }"#,
+            ),
+            (
+                "program/src/lib.rs",
+                "use common::{Config, Wrapper};
+                #[derive(BorshSchemaTS)]
+                pub struct Vault { config: Config, amount: Wrapper<u64>, range: (u8, u16) }",
+            ),
+            (
+                "common/Cargo.toml",
+                r#"[package]
+                name = "common""#,
+            ),
+            (
+                "common/src/lib.rs",
+                "#[derive(BorshSchemaTS)]
+                pub struct Wrapper<T> { inner: T }
+                #[derive(BorshSchemaTS)]
+                pub struct Config { fee: Wrapper<u64>, range: (u8, u16) }",
+            ),
+        ],
+    );
+    let layouts = generate_workspace_layouts(&root).unwrap();
+    let output = root.join("output");
+    let options = GeneratorOptions {
+        files: OutputFiles::Crates,
+        ..GeneratorOptions::default()
+    };
+    generate_output_with_options(&layouts, &output, &options).unwrap();
+
+    let program = fs::read_to_string(output.join("program.ts")).unwrap();
+    assert!(program.contains("import {Config} from './common';\n"));
+    assert!(program.contains("export class WrapperU64 extends Struct {"));
+    assert!(program.contains("export class TupleU8_U16 extends Struct {"));
+    let common = fs::read_to_string(output.join("common.ts")).unwrap();
+    assert!(common.contains("import {TupleU8_U16, WrapperU64} from './program';\n"));
+
+    // the modules import each other, so only the index refers to their
+    // classes outside of type annotations
+    for module in [&program, &common] {
+        assert!(!module.contains("SCHEMA"));
+    }
+    let index = fs::read_to_string(output.join("index.ts")).unwrap();
+    assert!(index.contains("import {Config} from './common';\n"));
+    assert!(index.contains("import {TupleU8_U16, Vault, WrapperU64} from './program';\n"));
+    assert!(index.contains("export const SCHEMA = new Map<any, any>([\n"));
+}
+
+#[test]
+fn missing_manifest() {
+    let root = write_files(
+        "workspace_missing_member",
+        &[(
+            "Cargo.toml",
+            r#"[package]
+            name = "broken"
+
+            [dependencies]
+            missing = { path = "missing" }"#,
+        )],
+    );
+    let err = generate_workspace_layouts(&root).unwrap_err().to_string();
+    assert!(err.starts_with("failed to read"));
+}
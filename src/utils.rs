@@ -4,92 +4,682 @@
 //! # Example
 //!
 //! ```rust
-//! # use agsol_borsh_schema::{generate_layouts, generate_output};
+//! # use agsol_borsh_schema::{generate_layouts, generate_output, validate_layouts};
 //! # fn generate_schema() -> Result<(), anyhow::Error> {
 //!     let layouts = generate_layouts("~/input-rust-directory")?;
+//!     validate_layouts(&layouts)?;
 //!     generate_output(&layouts, "./output-directory")?;
 //! # Ok(())
 //! # }
 //!
 //! ```
 
-use crate::layout::{Kind, Layout, LayoutField};
+use crate::layout::{
+    check_generic_uses, disambiguate, doc_comments, lift_tuples, monomorphize, sort_layouts,
+    validate, CfgSet, CodeStyle, CrateScope, Definitions, Diagnostic, Diagnostics, FieldNaming,
+    Kind, Layout, LayoutField, Location, ModuleScope, Nullability, PubkeyStrategy, TypeResolver,
+};
+use crate::workspace::collect_crates;
+use heck::SnakeCase;
 
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fs;
 use std::io::Write;
-use std::path::Path;
-
+use std::path::{Path, PathBuf};
 
 /// Traverses all files in the input directory and looks for data structures
 /// that should be converted into TypeScript and borsh layouts.
+///
+/// The module path of every layout is derived from the path of its file
+/// relative to `directory`, e.g. `state::accounts` for `state/accounts.rs`,
+/// where `mod.rs`, `lib.rs` and `main.rs` files belong to the module of their
+/// directory. Layouts with the same name declared in different files are
+/// disambiguated by these paths.
+///
+/// Type aliases declared anywhere in the directory are substituted by the
+/// type they stand for. `cfg` attributes are not evaluated, see
+/// [`generate_layouts_with_cfg`].
+///
+/// Syntax errors and errors in the data structures are collected from every
+/// file and returned at once as [`Diagnostics`], which can be retrieved from
+/// the error with `downcast_ref`.
 pub fn generate_layouts(directory: impl AsRef<Path>) -> Result<Vec<Layout>, anyhow::Error> {
-    let dir_items = fs::read_dir(directory)?;
-    Ok(dir_items
-        .flat_map(|item| {
-            let path = item?.path();
-            if path.is_dir() {
-                generate_layouts(path)
-            } else {
-                generate_layout_from_file(path)
-            }
-        })
-        .flatten()
-        .collect::<Vec<Layout>>())
+    directory_layouts(directory.as_ref(), None)
+}
+
+/// Same as [`generate_layouts`], but items, fields and variants that are
+/// inactive under `cfg` are ignored and the attributes of active `cfg_attr`s
+/// are applied.
+pub fn generate_layouts_with_cfg(
+    directory: impl AsRef<Path>,
+    cfg: &CfgSet,
+) -> Result<Vec<Layout>, anyhow::Error> {
+    directory_layouts(directory.as_ref(), Some(cfg))
+}
+
+fn directory_layouts(directory: &Path, cfg: Option<&CfgSet>) -> Result<Vec<Layout>, anyhow::Error> {
+    let mut sources = Sources::default();
+    parse_directory(directory, &[], cfg, &mut sources)?;
+    generate_layouts_from_files(&sources.files, sources.diagnostics)
 }
 
 /// Generates the TypeScript and borsh layouts of rust data structures found in `.rs`
 /// files.
 pub fn generate_layout_from_file(filepath: impl AsRef<Path>) -> Result<Vec<Layout>, anyhow::Error> {
-    if filepath.as_ref().extension() != Some(OsStr::new("rs")) {
-        return Ok(Vec::new());
+    let mut sources = Sources::default();
+    parse_file(filepath.as_ref(), Vec::new(), None, &mut sources)?;
+    generate_layouts_from_files(&sources.files, sources.diagnostics)
+}
+
+/// Looks for data structures in the module tree of a crate.
+///
+/// Parsing starts from the crate root (`src/lib.rs` or `src/main.rs` when
+/// `path` is the crate directory, or `path` itself when it is a file) and
+/// follows `mod foo;` declarations, including the ones with a `#[path]`
+/// attribute. Files that are not part of the module tree are ignored.
+///
+/// Every layout records its module path relative to the crate root, which is
+/// used for resolving `use` declarations when layouts with the same name are
+/// declared in multiple modules.
+pub fn generate_crate_layouts(path: impl AsRef<Path>) -> Result<Vec<Layout>, anyhow::Error> {
+    crate_layouts(path.as_ref(), None)
+}
+
+/// Same as [`generate_crate_layouts`], but evaluates `cfg` attributes, so
+/// modules declared by inactive `mod foo;` items are not parsed either.
+pub fn generate_crate_layouts_with_cfg(
+    path: impl AsRef<Path>,
+    cfg: &CfgSet,
+) -> Result<Vec<Layout>, anyhow::Error> {
+    crate_layouts(path.as_ref(), Some(cfg))
+}
+
+fn crate_layouts(path: &Path, cfg: Option<&CfgSet>) -> Result<Vec<Layout>, anyhow::Error> {
+    let entry = crate_entry(path)?;
+    let module_dir = entry.parent().unwrap_or_else(|| Path::new(""));
+    let mut sources = Sources::default();
+    parse_module_file(
+        &entry,
+        Vec::new(),
+        module_dir,
+        &CrateScope::default(),
+        cfg,
+        &mut sources,
+    )?;
+    generate_layouts_from_files(&sources.files, sources.diagnostics)
+}
+
+/// Looks for data structures in every member of a Cargo workspace and in the
+/// crates they depend on through `path` dependencies, so that layouts can
+/// refer to layouts declared in other crates.
+///
+/// `manifest_path` is the `Cargo.toml` of the workspace (or the directory
+/// containing it), which may also be a single package. Only the local
+/// manifests are read, dependencies from registries or git repositories are
+/// not scanned. The module path of every layout starts with the name of its
+/// crate, see [`OutputFiles::Crates`] for writing the layouts of each crate
+/// into a separate module.
+pub fn generate_workspace_layouts(
+    manifest_path: impl AsRef<Path>,
+) -> Result<Vec<Layout>, anyhow::Error> {
+    workspace_layouts(manifest_path.as_ref(), None)
+}
+
+/// Same as [`generate_workspace_layouts`], but evaluates `cfg` attributes.
+/// The same configuration is used for every crate.
+pub fn generate_workspace_layouts_with_cfg(
+    manifest_path: impl AsRef<Path>,
+    cfg: &CfgSet,
+) -> Result<Vec<Layout>, anyhow::Error> {
+    workspace_layouts(manifest_path.as_ref(), Some(cfg))
+}
+
+fn workspace_layouts(
+    manifest_path: &Path,
+    cfg: Option<&CfgSet>,
+) -> Result<Vec<Layout>, anyhow::Error> {
+    let mut sources = Sources::default();
+    for source_crate in collect_crates(manifest_path)? {
+        let module_dir = source_crate.entry.parent().unwrap_or_else(|| Path::new(""));
+        let crate_scope = CrateScope {
+            name: Some(source_crate.name.clone()),
+            externs: source_crate.externs,
+        };
+        parse_module_file(
+            &source_crate.entry,
+            vec![source_crate.name],
+            module_dir,
+            &crate_scope,
+            cfg,
+            &mut sources,
+        )?;
+    }
+    generate_layouts_from_files(&sources.files, sources.diagnostics)
+}
+
+/// Checks that the types referenced by the fields of the layouts are layouts
+/// themselves. Types that are neither primitives, nor aliases, nor declared
+/// with `BorshSchemaTS` in the scanned sources would otherwise end up in the
+/// generated TypeScript code as is.
+///
+/// The error lists every undefined type along with the file, the layout and
+/// the field it is used in.
+pub fn validate_layouts(layouts: &[Layout]) -> Result<(), anyhow::Error> {
+    validate(layouts)
+}
+
+/// A parsed source file and the path of the module it contains.
+pub(crate) struct SourceFile {
+    pub path: Option<PathBuf>,
+    pub code: String,
+    pub module_path: Vec<String>,
+    pub crate_scope: CrateScope,
+    pub syntax: syn::File,
+}
+
+/// The files parsed successfully and the errors found in the others.
+#[derive(Default)]
+struct Sources {
+    files: Vec<SourceFile>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Sources {
+    fn append(&mut self, other: &mut Sources) {
+        self.files.append(&mut other.files);
+        self.diagnostics.append(&mut other.diagnostics);
     }
-    let code = fs::read_to_string(filepath)?;
-    let syntax = syn::parse_file(&code)?;
+}
+
+/// Generates the layouts of the data structures declared in the files.
+/// Definitions declared in any of the files are visible in every file, but
+/// type aliases don't shadow layouts declared in other modules.
+///
+/// Errors are collected from every data structure and returned along with
+/// `diagnostics` as [`Diagnostics`].
+pub(crate) fn generate_layouts_from_files(
+    files: &[SourceFile],
+    mut diagnostics: Vec<Diagnostic>,
+) -> Result<Vec<Layout>, anyhow::Error> {
+    let mut definitions = Definitions::default();
+    for file in files {
+        definitions.collect(&file.syntax.items, &file.module_path);
+        declare_layouts(&file.syntax.items, &file.module_path, &mut definitions);
+    }
+    let resolver = TypeResolver::new(&definitions);
     let mut layouts = Vec::<Layout>::new();
-    for item in syntax.items {
+    for file in files {
+        let start = layouts.len();
+        let diagnostics_start = diagnostics.len();
+        generate_layouts_from_items(
+            &file.syntax.items,
+            &file.module_path,
+            &file.crate_scope,
+            &resolver,
+            &mut layouts,
+            &mut diagnostics,
+        );
+        for layout in &mut layouts[start..] {
+            layout.source = file.path.clone();
+        }
+        for diagnostic in &mut diagnostics[diagnostics_start..] {
+            diagnostic.set_source(file.path.as_deref(), &file.code);
+        }
+    }
+    Diagnostics(diagnostics).into_result()?;
+    disambiguate(&mut layouts)?;
+    Ok(layouts)
+}
+
+/// Finds the root file of a crate.
+fn crate_entry(path: &Path) -> Result<PathBuf, anyhow::Error> {
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+    ["src/lib.rs", "src/main.rs", "lib.rs", "main.rs"]
+        .iter()
+        .map(|entry| path.join(entry))
+        .find(|entry| entry.is_file())
+        .ok_or_else(|| anyhow::anyhow!("no `lib.rs` or `main.rs` found in `{}`", path.display()))
+}
+
+/// Parses the file of a module and the files of the modules it declares.
+/// `module_dir` is the directory that contains the files of its submodules.
+fn parse_module_file(
+    filepath: &Path,
+    module_path: Vec<String>,
+    module_dir: &Path,
+    crate_scope: &CrateScope,
+    cfg: Option<&CfgSet>,
+    sources: &mut Sources,
+) -> Result<(), anyhow::Error> {
+    let (code, syntax) = match parse_source(filepath, cfg, sources)? {
+        Some(parsed) => parsed,
+        None => return Ok(()),
+    };
+    let file_dir = filepath.parent().unwrap_or_else(|| Path::new(""));
+    let mut submodules = Sources::default();
+    parse_submodules(
+        &syntax.items,
+        &module_path,
+        file_dir,
+        module_dir,
+        crate_scope,
+        cfg,
+        &mut submodules,
+    )?;
+    sources.files.push(SourceFile {
+        path: Some(filepath.to_path_buf()),
+        code,
+        module_path,
+        crate_scope: crate_scope.clone(),
+        syntax,
+    });
+    sources.append(&mut submodules);
+    Ok(())
+}
+
+/// Parses the files of the modules declared by `mod foo;` items, recursing
+/// into inline modules. Paths in `#[path]` attributes are relative to
+/// `file_dir`.
+fn parse_submodules(
+    items: &[syn::Item],
+    module_path: &[String],
+    file_dir: &Path,
+    module_dir: &Path,
+    crate_scope: &CrateScope,
+    cfg: Option<&CfgSet>,
+    sources: &mut Sources,
+) -> Result<(), anyhow::Error> {
+    for item in items {
+        let item_mod = match item {
+            syn::Item::Mod(item_mod) => item_mod,
+            _ => continue,
+        };
+        let name = item_mod.ident.to_string();
+        let mut submodule_path = module_path.to_vec();
+        submodule_path.push(name.clone());
+        let submodule_dir = module_dir.join(&name);
+        if let Some((_, items)) = &item_mod.content {
+            parse_submodules(
+                items,
+                &submodule_path,
+                &submodule_dir,
+                &submodule_dir,
+                crate_scope,
+                cfg,
+                sources,
+            )?;
+            continue;
+        }
+        if let Some(path) = path_attribute(&item_mod.attrs)? {
+            // files included with `#[path]` behave like `mod.rs` files
+            let filepath = file_dir.join(path);
+            let dir = filepath
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .to_path_buf();
+            parse_module_file(&filepath, submodule_path, &dir, crate_scope, cfg, sources)?;
+            continue;
+        }
+        let candidates = [
+            module_dir.join(format!("{}.rs", name)),
+            submodule_dir.join("mod.rs"),
+        ];
+        let filepath = candidates
+            .iter()
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "file not found for module `{}`, expected `{}` or `{}`",
+                    submodule_path.join("::"),
+                    candidates[0].display(),
+                    candidates[1].display()
+                )
+            })?;
+        parse_module_file(
+            filepath,
+            submodule_path,
+            &submodule_dir,
+            crate_scope,
+            cfg,
+            sources,
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the value of a `#[path = "..."]` attribute.
+fn path_attribute(attrs: &[syn::Attribute]) -> Result<Option<String>, anyhow::Error> {
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("path")) {
+        if let syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(path),
+            ..
+        }) = attr.parse_meta()?
+        {
+            return Ok(Some(path.value()));
+        }
+    }
+    Ok(None)
+}
+
+/// Recursively parses all `.rs` files in a directory, which contains the
+/// files of the module at `module_path`.
+fn parse_directory(
+    directory: &Path,
+    module_path: &[String],
+    cfg: Option<&CfgSet>,
+    sources: &mut Sources,
+) -> Result<(), anyhow::Error> {
+    let mut paths = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    // `read_dir` returns the entries in a platform-dependent order
+    paths.sort();
+    for path in paths {
+        let mut file_module_path = module_path.to_vec();
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if path.is_dir() {
+            file_module_path.push(name);
+            parse_directory(&path, &file_module_path, cfg, sources)?;
+        } else {
+            if !matches!(name.as_str(), "mod" | "lib" | "main") {
+                file_module_path.push(name);
+            }
+            parse_file(&path, file_module_path, cfg, sources)?;
+        }
+    }
+    Ok(())
+}
+
+/// Parses a `.rs` file containing the module at `module_path`, other files
+/// are ignored.
+fn parse_file(
+    filepath: &Path,
+    module_path: Vec<String>,
+    cfg: Option<&CfgSet>,
+    sources: &mut Sources,
+) -> Result<(), anyhow::Error> {
+    if filepath.extension() != Some(OsStr::new("rs")) {
+        return Ok(());
+    }
+    if let Some((code, syntax)) = parse_source(filepath, cfg, sources)? {
+        sources.files.push(SourceFile {
+            path: Some(filepath.to_path_buf()),
+            code,
+            module_path,
+            crate_scope: CrateScope::default(),
+            syntax,
+        });
+    }
+    Ok(())
+}
+
+/// Reads and parses a source file. Syntax errors are collected into
+/// `sources`, in which case `None` is returned.
+fn parse_source(
+    filepath: &Path,
+    cfg: Option<&CfgSet>,
+    sources: &mut Sources,
+) -> Result<Option<(String, syn::File)>, anyhow::Error> {
+    let code = fs::read_to_string(filepath)
+        .map_err(|err| anyhow::anyhow!("failed to read `{}`: {}", filepath.display(), err))?;
+    let parsed = syn::parse_file(&code)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut syntax| {
+            configure(&mut syntax, cfg)?;
+            Ok(syntax)
+        });
+    match parsed {
+        Ok(syntax) => Ok(Some((code, syntax))),
+        Err(err) => {
+            for mut diagnostic in Diagnostic::from_error(err) {
+                diagnostic.set_source(Some(filepath), &code);
+                sources.diagnostics.push(diagnostic);
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Removes the items that are inactive under `cfg`, if any.
+fn configure(syntax: &mut syn::File, cfg: Option<&CfgSet>) -> Result<(), anyhow::Error> {
+    match cfg {
+        Some(cfg) => cfg.configure_items(&mut syntax.items),
+        None => Ok(()),
+    }
+}
+
+/// Generates the layouts of the items declared in a module, recursing into
+/// inline modules. Errors are collected into `diagnostics`.
+fn generate_layouts_from_items(
+    items: &[syn::Item],
+    module_path: &[String],
+    crate_scope: &CrateScope,
+    resolver: &TypeResolver,
+    layouts: &mut Vec<Layout>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let scope = ModuleScope::new(module_path, items, crate_scope);
+    let resolver = resolver.with_scope(&scope);
+    let resolver = &resolver;
+    for item in items {
         match item {
-            syn::Item::Struct(ref item_struct) => {
-                for attr in &item_struct.attrs {
-                    let attribute_string = attr.tokens.to_string();
-                    if attribute_string.contains(crate::ATTRIBUTE_LABEL) {
-                        layouts.push(Layout::from_tokens(
-                            &item_struct.ident.to_string(),
-                            &mut item_struct.fields.iter(),
-                        )?);
+            syn::Item::Struct(ref item_struct) if derives_schema(&item_struct.attrs) => {
+                let name = item_struct.ident.to_string();
+                match Layout::from_tokens(
+                    &name,
+                    &mut item_struct.fields.iter(),
+                    &item_struct.generics,
+                    &resolver.with_self(&item_struct.ident, &item_struct.generics),
+                ) {
+                    Ok(mut layout) => {
+                        layout.module_path = module_path.to_vec();
+                        layout.crate_name = crate_scope.name.clone();
+                        layout.location = Some(Location::from_span(item_struct.ident.span()));
+                        layout.docs = doc_comments(&item_struct.attrs);
+                        layouts.push(layout);
                     }
+                    Err(err) => push_diagnostics(diagnostics, err, item_struct.ident.span(), &name),
                 }
             }
-            syn::Item::Enum(ref item_enum) => {
-                for attr in &item_enum.attrs {
-                    let attribute_string = attr.tokens.to_string();
-                    if attribute_string.contains(crate::ATTRIBUTE_LABEL) {
-                        let mut enum_layout = Layout {
-                            name: item_enum.ident.to_string(),
-                            kind: Kind::Enum,
-                            fields: Vec::new(),
-                        };
-                        let parent_name = item_enum.ident.to_string();
-                        let mut variant_layouts = item_enum
-                            .variants
-                            .iter()
-                            .map(|variant| {
-                                let name = parent_name.clone() + &variant.ident.to_string();
-                                enum_layout
-                                    .fields
-                                    .push(LayoutField::from_enum_variant(&name)?);
-                                Layout::from_tokens(&name, &mut variant.fields.iter())
-                            })
-                            .collect::<Result<Vec<_>, _>>()?;
-                        layouts.push(enum_layout);
-                        layouts.append(&mut variant_layouts);
+            syn::Item::Enum(ref item_enum) if derives_schema(&item_enum.attrs) => {
+                let parent_name = item_enum.ident.to_string();
+                let discriminants = enum_discriminants(item_enum, resolver).unwrap_or_else(|err| {
+                    push_diagnostics(diagnostics, err, item_enum.ident.span(), &parent_name);
+                    Vec::new()
+                });
+                let mut enum_layout = Layout {
+                    name: parent_name.clone(),
+                    kind: Kind::Enum,
+                    fields: Vec::new(),
+                    generics: item_enum
+                        .generics
+                        .type_params()
+                        .map(|param| param.ident.to_string())
+                        .collect(),
+                    discriminants,
+                    module_path: module_path.to_vec(),
+                    crate_name: crate_scope.name.clone(),
+                    source: None,
+                    location: Some(Location::from_span(item_enum.ident.span())),
+                    docs: doc_comments(&item_enum.attrs),
+                };
+                let mut variant_layouts = Vec::new();
+                // `Self` refers to the `enum` in the fields of its variants
+                let variant_resolver = resolver.with_self(&item_enum.ident, &item_enum.generics);
+                for variant in &item_enum.variants {
+                    let name = parent_name.clone() + &variant.ident.to_string();
+                    let layout = LayoutField::from_enum_variant(&name, &enum_layout.generics)
+                        .and_then(|mut field| {
+                            field.docs = doc_comments(&variant.attrs);
+                            enum_layout.fields.push(field);
+                            Layout::from_tokens(
+                                &name,
+                                &mut variant.fields.iter(),
+                                &item_enum.generics,
+                                &variant_resolver,
+                            )
+                        });
+                    match layout {
+                        Ok(mut layout) => {
+                            layout.module_path = module_path.to_vec();
+                            layout.crate_name = crate_scope.name.clone();
+                            layout.location = Some(Location::from_span(variant.ident.span()));
+                            layout.docs = doc_comments(&variant.attrs);
+                            variant_layouts.push(layout);
+                        }
+                        Err(err) => push_diagnostics(diagnostics, err, variant.ident.span(), &name),
                     }
                 }
+                layouts.push(enum_layout);
+                layouts.append(&mut variant_layouts);
+            }
+            syn::Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    let mut module_path = module_path.to_vec();
+                    module_path.push(item_mod.ident.to_string());
+                    generate_layouts_from_items(
+                        items,
+                        &module_path,
+                        crate_scope,
+                        resolver,
+                        layouts,
+                        diagnostics,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collects the diagnostics of an error found in `item`. Errors without a
+/// location are located at `span`.
+fn push_diagnostics(
+    diagnostics: &mut Vec<Diagnostic>,
+    err: anyhow::Error,
+    span: proc_macro2::Span,
+    item: &str,
+) {
+    diagnostics.extend(
+        Diagnostic::from_error(err)
+            .into_iter()
+            .map(|diagnostic| diagnostic.at(span).in_item(item)),
+    );
+}
+
+/// Registers the layouts declared in a module and its inline modules.
+fn declare_layouts(items: &[syn::Item], module_path: &[String], definitions: &mut Definitions) {
+    for item in items {
+        match item {
+            syn::Item::Struct(item_struct) if derives_schema(&item_struct.attrs) => {
+                definitions.declare_layout(&item_struct.ident.to_string(), module_path);
+            }
+            syn::Item::Enum(item_enum) if derives_schema(&item_enum.attrs) => {
+                let name = item_enum.ident.to_string();
+                definitions.declare_layout(&name, module_path);
+                for variant in &item_enum.variants {
+                    definitions
+                        .declare_layout(&(name.clone() + &variant.ident.to_string()), module_path);
+                }
+            }
+            syn::Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    let mut module_path = module_path.to_vec();
+                    module_path.push(item_mod.ident.to_string());
+                    declare_layouts(items, &module_path, definitions);
+                }
             }
             _ => {}
         }
     }
-    Ok(layouts)
+}
+
+/// Returns `true` if the attributes derive [`BorshSchemaTS`](crate::BorshSchemaTS),
+/// either directly or through `cfg_attr`.
+fn derives_schema(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter_map(|attr| attr.parse_meta().ok())
+        .any(|meta| meta_derives_schema(&meta))
+}
+
+fn meta_derives_schema(meta: &syn::Meta) -> bool {
+    let list = match meta {
+        syn::Meta::List(list) => list,
+        _ => return false,
+    };
+    let nested_metas = list.nested.iter().filter_map(|nested| match nested {
+        syn::NestedMeta::Meta(meta) => Some(meta),
+        syn::NestedMeta::Lit(_) => None,
+    });
+    if list.path.is_ident("derive") {
+        nested_metas
+            .filter_map(|meta| match meta {
+                syn::Meta::Path(path) => path.segments.last(),
+                _ => None,
+            })
+            .any(|segment| segment.ident == crate::ATTRIBUTE_LABEL)
+    } else if list.path.is_ident("cfg_attr") {
+        // the first argument is the configuration predicate
+        nested_metas.skip(1).any(meta_derives_schema)
+    } else {
+        false
+    }
+}
+
+/// Returns the serialized discriminant of every variant of an `enum`.
+///
+/// Variants are serialized by their position unless the `enum` is annotated
+/// with `#[borsh(use_discriminant = true)]`, in which case explicit
+/// discriminants are used and implicit ones count up from the previous one.
+fn enum_discriminants(
+    item_enum: &syn::ItemEnum,
+    resolver: &TypeResolver,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let mut use_discriminant = false;
+    for attr in item_enum
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("borsh"))
+    {
+        if let syn::Meta::List(list) = attr.parse_meta()? {
+            for meta in list.nested {
+                if let syn::NestedMeta::Meta(syn::Meta::NameValue(name_value)) = meta {
+                    if let (true, syn::Lit::Bool(value)) = (
+                        name_value.path.is_ident("use_discriminant"),
+                        &name_value.lit,
+                    ) {
+                        use_discriminant = value.value;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut discriminants = Vec::new();
+    let mut next = 0_u16;
+    for variant in &item_enum.variants {
+        let discriminant = match &variant.discriminant {
+            Some((_, expr)) if use_discriminant => u16::from(resolver.eval_discriminant(expr)?),
+            _ => next,
+        };
+        let discriminant = u8::try_from(discriminant).map_err(|_| {
+            anyhow::anyhow!(
+                "variant `{}::{}` has a discriminant larger than 255",
+                item_enum.ident,
+                variant.ident
+            )
+        })?;
+        discriminants.push(discriminant);
+        next = u16::from(discriminant) + 1;
+    }
+    Ok(discriminants)
 }
 
 static LIB_SCHEMA_PKEY: &str = r#"
@@ -102,35 +692,634 @@ static LIB_SCHEMA_PKEY: &str = r#"
   ]
 "#;
 
-static LIB_PREABMLE: &str = r#"import {Struct, Enum, PublicKeyBE} from 'ts-borsh-schema';
+static LIB_RUNTIME: &str = r#"import {deserialize as borshDeserialize, serialize as borshSerialize} from 'borsh';
+import BN from 'bn.js';
+
+export {BN};
+
+/** Maps the generated classes to their borsh schemas. */
+export type Schema = Map<Function, any>;
+
+/** Base class of the generated classes of structs. */
+export class Struct {
+  constructor(properties: any) {
+    Object.assign(this, properties);
+  }
+}
+
+/**
+ * Base class of the generated classes of enums. An instance holds a single
+ * variant, the name of which is stored in `enum`.
+ */
+export class Enum {
+  enum!: string;
+
+  constructor(properties: any) {
+    const variants = Object.keys(properties);
+    if (variants.length !== 1) {
+      throw new Error('an enum must be constructed with exactly one variant');
+    }
+    Object.assign(this, properties);
+    this.enum = variants[0];
+  }
+}
+
+/** A public key serialized as its 32 bytes. */
+export class PublicKeyBE extends Struct {
+  declare value: Uint8Array;
+
+  static fromBytes(bytes: Uint8Array): PublicKeyBE {
+    if (bytes.length !== 32) {
+      throw new Error(`a public key has 32 bytes, found ${bytes.length}`);
+    }
+    return new PublicKeyBE({value: bytes});
+  }
+
+  toBytes(): Uint8Array {
+    return this.value;
+  }
+}
+
+/** Serializes an instance of a generated class. */
+export function serialize(schema: Schema, object: Struct | Enum): Uint8Array {
+  return borshSerialize(schema, object);
+}
+
+/**
+ * Deserializes an instance of a generated class. `bytes` is passed to borsh
+ * as is, so the runtime works without the Node.js `Buffer`.
+ */
+export function deserialize<T>(
+  schema: Schema,
+  classType: {new (properties: any): T},
+  bytes: Parameters<typeof borshDeserialize>[2],
+): T {
+  return borshDeserialize(schema, classType, bytes);
+}
+"#;
+
+static LIB_BORSH_IMPORTS: &str = r#"import {BinaryReader, BinaryWriter} from 'borsh';
+"#;
+
+static LIB_WEB3_PUBKEY: &str = r#"
+(BinaryReader.prototype as any).readPubkey = function (this: BinaryReader): PublicKey {
+  return new PublicKey(this.readFixedArray(32));
+};
+(BinaryWriter.prototype as any).writePubkey = function (this: BinaryWriter, value: PublicKey): void {
+  this.writeFixedArray(value.toBytes());
+};
+"#;
+
+static LIB_BASE58_IMPORTS: &str = r#"import {BinaryReader, BinaryWriter} from 'borsh';
+import bs58 from 'bs58';
 "#;
+
+static LIB_BASE58_PUBKEY: &str = r#"
+(BinaryReader.prototype as any).readBase58Pubkey = function (this: BinaryReader): string {
+  return bs58.encode(this.readFixedArray(32));
+};
+(BinaryWriter.prototype as any).writeBase58Pubkey = function (this: BinaryWriter, value: string): void {
+  this.writeFixedArray(bs58.decode(value));
+};
+"#;
+
+static LIB_REJECT_NAN: &str = r#"
+function rejectNaN(value: any, field: string): void {
+  if (typeof value === 'number' && Number.isNaN(value)) {
+    throw new Error(`NaN is not a valid value for field '${field}'`);
+  }
+  if (Array.isArray(value)) {
+    value.forEach((item) => rejectNaN(item, field));
+  } else if (value instanceof Map) {
+    value.forEach((item, key) => {
+      rejectNaN(key, field);
+      rejectNaN(item, field);
+    });
+  }
+}
+
+"#;
+
+/// Determines how generic `struct`s and `enum`s are converted into TypeScript.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GenericsMode {
+    /// Emits a separate, non-generic class and schema for every concrete
+    /// instantiation found in the layouts, e.g. `Wrapper<u64>` becomes
+    /// `WrapperU64`.
+    #[default]
+    Monomorphize,
+    /// Emits a generic TypeScript class and a function that builds the schema
+    /// from the schemas of the type arguments, e.g. `wrapperSchema('u64')`.
+    /// The schemas of generic layouts have to be registered manually, so
+    /// layouts that aren't generic can't refer to generic ones in this mode.
+    Generic,
+}
+
+/// Determines how the generated code is split into TypeScript modules.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFiles {
+    /// Writes every layout into a single file, `schema.ts` by default.
+    #[default]
+    Single,
+    /// Writes the layouts of every crate into a separate `<crate>.ts` module
+    /// and an `index.ts` like [`OutputFiles::Modules`]. Layouts that don't
+    /// belong to a crate (see [`generate_workspace_layouts`]) are written into
+    /// `schema.ts`.
+    ///
+    /// Crates can use the same instantiations of generic layouts and the same
+    /// tuples, which are written into the module of one of them, so modules
+    /// only import the classes of each other and the combined `SCHEMA` is
+    /// built in `index.ts`.
+    Crates,
+    /// Writes the layouts of every Rust module into a separate file that
+    /// mirrors the module path, e.g. `state/accounts.ts` for the layouts
+    /// declared in `state::accounts`. Layouts declared in the root module are
+    /// written into `schema.ts`.
+    ///
+    /// Every file exports its classes and imports the classes it refers to
+    /// from the other files. `index.ts` re-exports every file and builds the
+    /// combined `SCHEMA`.
+    Modules,
+    /// Writes every layout into a separate file named after the layout in the
+    /// directory of its module, e.g. `state/user_account.ts`, and an
+    /// `index.ts` like [`OutputFiles::Modules`].
+    Layouts,
+}
+
+impl OutputFiles {
+    /// Returns the path of the file the layout is written into, relative to
+    /// the output directory and without the extension. Layouts of the root
+    /// module are written into `root_file`.
+    fn file_path(self, layout: &Layout, root_file: &str) -> Vec<String> {
+        let mut path = layout.module_path.clone();
+        match self {
+            Self::Crates => {
+                return vec![layout
+                    .crate_name
+                    .clone()
+                    .unwrap_or_else(|| root_file.to_owned())]
+            }
+            Self::Layouts => path.push(layout.name.to_snake_case()),
+            _ if path.is_empty() => path.push(root_file.to_owned()),
+            _ => {}
+        }
+        path
+    }
+}
+
+/// Options that control the generated output. The default options generate
+/// a single `schema.ts` for `ts-borsh-schema`.
+///
+/// # Example
+///
+/// ```rust
+/// # use agsol_borsh_schema::{FieldNaming, GeneratorOptions, Nullability, OutputFiles};
+/// let options = GeneratorOptions::new()
+///     .with_files(OutputFiles::Modules)
+///     .with_file_name("layouts.ts")
+///     .with_library("@my-org/borsh")
+///     .with_schema_name("LAYOUTS")
+///     .with_field_naming(FieldNaming::SnakeCase)
+///     .with_nullability(Nullability::Optional)
+///     .with_indent("    ");
+/// ```
+#[derive(Clone, Debug)]
+pub struct GeneratorOptions {
+    pub generics: GenericsMode,
+    pub files: OutputFiles,
+    /// The file written with [`OutputFiles::Single`]. Without the extension,
+    /// it's also the name of the module of the layouts that don't belong to a
+    /// crate or are declared in the root module.
+    pub file_name: String,
+    /// The module `Struct`, `Enum` and `PublicKeyBE` are imported from.
+    pub library: String,
+    /// Writes a `runtime.ts` with `Struct`, `Enum`, `PublicKeyBE` and typed
+    /// `serialize` and `deserialize` functions into the output directory,
+    /// which is imported instead of `library`. The generated code then only
+    /// depends on `borsh` and `bn.js` (besides the modules needed for
+    /// [`PubkeyStrategy`]).
+    pub runtime: bool,
+    /// Name of the exported map of the schemas.
+    pub schema_name: String,
+    pub style: CodeStyle,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            generics: GenericsMode::default(),
+            files: OutputFiles::default(),
+            file_name: "schema.ts".to_owned(),
+            library: "ts-borsh-schema".to_owned(),
+            runtime: false,
+            schema_name: "SCHEMA".to_owned(),
+            style: CodeStyle::default(),
+        }
+    }
+}
+
+impl GeneratorOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how generic layouts are converted.
+    pub fn with_generics(mut self, generics: GenericsMode) -> Self {
+        self.generics = generics;
+        self
+    }
+
+    /// Sets how the output is split into files.
+    pub fn with_files(mut self, files: OutputFiles) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Sets the name of the generated file, e.g. `schema.ts`.
+    pub fn with_file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
+    /// Sets the module the base classes are imported from.
+    pub fn with_library(mut self, library: impl Into<String>) -> Self {
+        self.library = library.into();
+        self
+    }
+
+    /// Sets whether a self-contained runtime is written instead of importing
+    /// `library`.
+    pub fn with_runtime(mut self, runtime: bool) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// Sets the name of the exported map of the schemas.
+    pub fn with_schema_name(mut self, schema_name: impl Into<String>) -> Self {
+        self.schema_name = schema_name.into();
+        self
+    }
+
+    /// Sets the naming convention of the fields.
+    pub fn with_field_naming(mut self, field_naming: FieldNaming) -> Self {
+        self.style.field_naming = field_naming;
+        self
+    }
+
+    /// Sets how the fields of the classes are declared.
+    pub fn with_nullability(mut self, nullability: Nullability) -> Self {
+        self.style.nullability = nullability;
+        self
+    }
+
+    /// Sets how public keys are represented.
+    pub fn with_pubkey(mut self, pubkey: PubkeyStrategy) -> Self {
+        self.style.pubkey = pubkey;
+        self
+    }
+
+    /// Sets a single level of indentation of the classes, the schema map and
+    /// the schema factories.
+    pub fn with_indent(mut self, indent: impl Into<String>) -> Self {
+        self.style.indent = indent.into();
+        self
+    }
+
+    /// Returns the module the file at `file` (see [`OutputFiles::file_path`])
+    /// imports the base classes from.
+    fn library_for(&self, file: &[String]) -> String {
+        if self.runtime {
+            relative_import(file, &[RUNTIME_MODULE.to_owned()])
+        } else {
+            self.library.clone()
+        }
+    }
+
+    /// Returns the name of the generated module without the extension.
+    fn module_name(&self) -> &str {
+        self.file_name
+            .strip_suffix(".ts")
+            .unwrap_or(&self.file_name)
+    }
+}
+
 /// Writes the generated layouts into a file in the provided output directory.
+///
+/// The output doesn't depend on the order of `layouts`: the classes are
+/// sorted by module and name, and declared after the classes they refer to
+/// where possible.
+///
+/// The layouts are checked with [`validate_layouts`] first, nothing is written
+/// if they refer to undefined types.
 pub fn generate_output(
     layouts: &[Layout],
     output_directory: impl AsRef<Path>,
 ) -> Result<(), anyhow::Error> {
-    let schema_string = layouts
+    generate_output_with_options(layouts, output_directory, &GeneratorOptions::default())
+}
+
+/// The name of the module written with [`GeneratorOptions::runtime`].
+const RUNTIME_MODULE: &str = "runtime";
+
+/// Writes the generated layouts into a file in the provided output directory
+/// using custom [`GeneratorOptions`].
+pub fn generate_output_with_options(
+    layouts: &[Layout],
+    output_directory: impl AsRef<Path>,
+    options: &GeneratorOptions,
+) -> Result<(), anyhow::Error> {
+    validate(layouts)?;
+    let layouts = match options.generics {
+        GenericsMode::Monomorphize => monomorphize(layouts)?,
+        GenericsMode::Generic => {
+            check_generic_uses(layouts)?;
+            layouts.to_vec()
+        }
+    };
+    let layouts = sort_layouts(&lift_tuples(&layouts)?);
+
+    fs::create_dir_all(&output_directory)?;
+    match options.files {
+        OutputFiles::Single => {
+            if options.runtime && options.module_name() == RUNTIME_MODULE {
+                return Err(reserved_module_error(RUNTIME_MODULE, "the runtime"));
+            }
+            let file = [options.module_name().to_owned()];
+            let module = generate_module(&layouts, "", "", &file, options)?;
+            let mut file = fs::File::create(output_directory.as_ref().join(&options.file_name))?;
+            write!(file, "{}", module)?;
+        }
+        files => generate_split_modules(&layouts, output_directory.as_ref(), files, options)?,
+    }
+    if options.runtime {
+        fs::write(
+            output_directory
+                .as_ref()
+                .join(RUNTIME_MODULE)
+                .with_extension("ts"),
+            options.style.reindent(LIB_RUNTIME),
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the layouts referenced by `module_layouts` that are declared in
+/// other modules, grouped by the module they are declared in. `owners` maps
+/// the name of every layout to the index of its module.
+fn imported_layouts<'a>(
+    module: usize,
+    module_layouts: &[Layout],
+    owners: &HashMap<&'a str, usize>,
+) -> Vec<(usize, Vec<&'a str>)> {
+    let mut imported = Vec::<(usize, Vec<&str>)>::new();
+    for layout in module_layouts {
+        let mut referenced = Vec::new();
+        for field in &layout.fields {
+            field.ty().referenced_layouts(&mut referenced);
+        }
+        for name in referenced {
+            let (name, owner) = match owners.get_key_value(name.as_str()) {
+                Some((name, owner)) if *owner != module => (*name, *owner),
+                _ => continue,
+            };
+            match imported.iter_mut().find(|(module, _)| *module == owner) {
+                Some((_, names)) if !names.contains(&name) => names.push(name),
+                Some(_) => {}
+                None => imported.push((owner, vec![name])),
+            }
+        }
+    }
+    imported.sort();
+    for (_, names) in &mut imported {
+        names.sort_unstable();
+    }
+    imported
+}
+
+/// Writes the layouts into the files given by `files` and an `index.ts` that
+/// re-exports them, see [`OutputFiles::Modules`].
+///
+/// The files only refer to the imported classes in type annotations and in
+/// the bodies of schema factories, so imports between files may be cyclic.
+fn generate_split_modules(
+    layouts: &[Layout],
+    output_directory: &Path,
+    files: OutputFiles,
+    options: &GeneratorOptions,
+) -> Result<(), anyhow::Error> {
+    let mut modules = Vec::<(Vec<String>, Vec<Layout>)>::new();
+    for layout in layouts {
+        let file_path = files.file_path(layout, options.module_name());
+        match modules.iter_mut().find(|(path, _)| *path == file_path) {
+            Some((_, module_layouts)) => module_layouts.push(layout.clone()),
+            None => modules.push((file_path, vec![layout.clone()])),
+        }
+    }
+    modules.sort_by(|(a, _), (b, _)| a.cmp(b));
+    if modules.iter().any(|(path, _)| path == &["index"]) {
+        return Err(reserved_module_error("index", "the combined schema"));
+    }
+    if options.runtime && modules.iter().any(|(path, _)| path == &[RUNTIME_MODULE]) {
+        return Err(reserved_module_error(RUNTIME_MODULE, "the runtime"));
+    }
+    let owners = modules
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (_, module_layouts))| {
+            module_layouts
+                .iter()
+                .map(move |layout| (layout.name.as_str(), i))
+        })
+        .collect::<HashMap<_, _>>();
+
+    for (i, (path, module_layouts)) in modules.iter().enumerate() {
+        let imported = imported_layouts(i, module_layouts, &owners);
+        let imports = imported
+            .iter()
+            .map(|(module, names)| {
+                format!(
+                    "import {{{}}} from '{}';\n",
+                    names.join(", "),
+                    relative_import(path, &modules[*module].0)
+                )
+            })
+            .collect::<String>();
+        let module = generate_classes(module_layouts, &imports, "", path, options)?;
+        let file_path = output_directory
+            .join(path.iter().collect::<PathBuf>())
+            .with_extension("ts");
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file_path, module)?;
+    }
+
+    let (registration_imports, registration) = pubkey_registration(options.style.pubkey);
+    let mut index = pubkey_import(&["index".to_owned()], options) + registration_imports;
+    for (path, module_layouts) in &modules {
+        let mut names = module_layouts
+            .iter()
+            .filter(|layout| !layout.is_generic())
+            .map(|layout| layout.name.as_str())
+            .collect::<Vec<_>>();
+        if !names.is_empty() {
+            names.sort_unstable();
+            index.push_str(&format!(
+                "import {{{}}} from './{}';\n",
+                names.join(", "),
+                path.join("/")
+            ));
+        }
+    }
+    index.push('\n');
+    for (path, _) in &modules {
+        index.push_str(&format!("export * from './{}';\n", path.join("/")));
+    }
+    index.push_str(&options.style.reindent(registration));
+    index.push('\n');
+    index.push_str(&generate_schema(layouts, "", options)?);
+    fs::write(output_directory.join("index.ts"), index)?;
+    Ok(())
+}
+
+fn reserved_module_error(module: &str, reason: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "can't write layouts into `{}.ts`, because it is reserved for {}",
+        module,
+        reason
+    )
+}
+
+/// Returns the path that the file at `to` is imported by from the file at
+/// `from`, both given relative to the output directory without extension.
+fn relative_import(from: &[String], to: &[String]) -> String {
+    let from_dir = &from[..from.len() - 1];
+    let to_dir = &to[..to.len() - 1];
+    let common = from_dir
         .iter()
-        .map(|layout| layout.to_borsh_schema())
-        .collect::<String>();
+        .zip(to_dir)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut path = if common == from_dir.len() {
+        String::from("./")
+    } else {
+        "../".repeat(from_dir.len() - common)
+    };
+    path.push_str(&to[common..].join("/"));
+    path
+}
+
+/// Generates a TypeScript module containing the classes and the schema of
+/// the layouts. `imports` are added after the library imports, and
+/// `schema_imports` are the entries of imported schemas.
+fn generate_module(
+    layouts: &[Layout],
+    imports: &str,
+    schema_imports: &str,
+    file: &[String],
+    options: &GeneratorOptions,
+) -> Result<String, anyhow::Error> {
+    let (registration_imports, registration) = pubkey_registration(options.style.pubkey);
+    let imports = registration_imports.to_owned() + imports;
+    Ok(
+        generate_classes(layouts, &imports, registration, file, options)?
+            + &generate_schema(layouts, schema_imports, options)?,
+    )
+}
 
+/// Generates the library imports, `imports`, the `registration` of custom
+/// field types and the classes of the layouts, along with the schema
+/// factories of generic layouts. `file` is the path of the generated module,
+/// see [`OutputFiles::file_path`].
+fn generate_classes(
+    layouts: &[Layout],
+    imports: &str,
+    registration: &str,
+    file: &[String],
+    options: &GeneratorOptions,
+) -> Result<String, anyhow::Error> {
     let classes_string = layouts
         .iter()
-        .map(|layout| layout.to_ts_class())
-        .collect::<String>();
+        .map(|layout| layout.to_ts_class_with_style(&options.style))
+        .chain(
+            layouts
+                .iter()
+                .filter(|layout| layout.is_generic())
+                .map(|layout| layout.to_schema_factory_with_style(&options.style)),
+        )
+        .collect::<Result<String, _>>()?;
 
+    let library = options.library_for(file);
+    // the runtime also provides the type of `u64` and wider integers
+    let bn = if options.runtime { ", BN" } else { "" };
+    let mut preamble = match options.style.pubkey {
+        PubkeyStrategy::PublicKeyBE => format!(
+            "import {{Struct, Enum, PublicKeyBE{}}} from '{}';\n",
+            bn, library
+        ),
+        _ => {
+            format!("import {{Struct, Enum{}}} from '{}';\n", bn, library)
+                + &pubkey_import(file, options)
+        }
+    };
+    preamble.push_str(imports);
+    preamble.push_str(&options.style.reindent(registration));
+    if layouts.iter().any(|layout| layout.has_float_fields()) {
+        preamble.push_str(&options.style.reindent(LIB_REJECT_NAN));
+    }
 
-    let schema = format!(
-        r#"export const SCHEMA = new Map<any, any>([{}
-{}
+    Ok(preamble + &classes_string)
+}
+
+/// Generates the `SCHEMA` of the non-generic layouts, which starts with
+/// `schema_imports`.
+fn generate_schema(
+    layouts: &[Layout],
+    schema_imports: &str,
+    options: &GeneratorOptions,
+) -> Result<String, anyhow::Error> {
+    let schema_string = layouts
+        .iter()
+        .filter(|layout| !layout.is_generic())
+        .map(|layout| layout.to_borsh_schema_with_style(&options.style))
+        .collect::<Result<String, _>>()?;
+
+    // only `PublicKeyBE` is a class that needs a schema
+    let pubkey_schema = match options.style.pubkey {
+        PubkeyStrategy::PublicKeyBE => {
+            String::from("\n") + &options.style.reindent(LIB_SCHEMA_PKEY)
+        }
+        _ => String::new(),
+    };
+    Ok(format!(
+        r#"export const {} = new Map<any, any>([{}{}{}
 ]);"#,
-        schema_string, LIB_SCHEMA_PKEY
-    );
+        options.schema_name, schema_imports, schema_string, pubkey_schema
+    ))
+}
 
-    let imports = String::from(LIB_PREABMLE);
+/// Returns the import of the class of public keys into the module at
+/// `file`.
+fn pubkey_import(file: &[String], options: &GeneratorOptions) -> String {
+    match options.style.pubkey {
+        PubkeyStrategy::PublicKeyBE => {
+            format!(
+                "import {{PublicKeyBE}} from '{}';\n",
+                options.library_for(file)
+            )
+        }
+        PubkeyStrategy::Web3 => String::from("import {PublicKey} from '@solana/web3.js';\n"),
+        PubkeyStrategy::Bytes | PubkeyStrategy::Base58 => String::new(),
+    }
+}
 
-    fs::create_dir_all(&output_directory)?;
-    let mut file = fs::File::create(output_directory.as_ref().join("schema.ts"))?;
-    write!(file, "{}", imports + &classes_string + &schema)?;
-    Ok(())
+/// Returns the imports and the code that registers the field type of public
+/// keys with `borsh`, if the representation of public keys needs one.
+fn pubkey_registration(pubkey: PubkeyStrategy) -> (&'static str, &'static str) {
+    match pubkey {
+        PubkeyStrategy::Web3 => (LIB_BORSH_IMPORTS, LIB_WEB3_PUBKEY),
+        PubkeyStrategy::Base58 => (LIB_BASE58_IMPORTS, LIB_BASE58_PUBKEY),
+        PubkeyStrategy::PublicKeyBE | PubkeyStrategy::Bytes => ("", ""),
+    }
 }
@@ -0,0 +1,183 @@
+//! Discovery of the crates of a Cargo workspace and of their path
+//! dependencies by reading the `Cargo.toml` manifests. Nothing is fetched,
+//! dependencies from registries or git repositories are ignored.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A crate whose sources are scanned for layouts.
+pub struct SourceCrate {
+    /// The name of the crate as used in Rust code, e.g. `my_common` for the
+    /// `my-common` package.
+    pub name: String,
+    /// The root file of the crate.
+    pub entry: PathBuf,
+    /// The names under which the collected dependencies of the crate are
+    /// available in its source, mapped to the names of the dependencies.
+    pub externs: HashMap<String, String>,
+}
+
+/// A parsed `Cargo.toml`.
+struct Manifest {
+    dir: PathBuf,
+    toml: toml::Value,
+}
+
+impl Manifest {
+    fn read(path: &Path) -> Result<Self, anyhow::Error> {
+        let path = if path.is_dir() {
+            path.join("Cargo.toml")
+        } else {
+            path.to_path_buf()
+        };
+        let code = fs::read_to_string(&path)
+            .map_err(|err| anyhow::anyhow!("failed to read `{}`: {}", path.display(), err))?;
+        let toml = code
+            .parse::<toml::Value>()
+            .map_err(|err| anyhow::anyhow!("failed to parse `{}`: {}", path.display(), err))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+        Ok(Self { dir, toml })
+    }
+
+    fn table(&self, path: &[&str]) -> Option<&toml::value::Table> {
+        path.iter()
+            .try_fold(&self.toml, |value, key| value.get(key))?
+            .as_table()
+    }
+
+    fn strings(&self, path: &[&str], key: &str) -> Vec<String> {
+        self.table(path)
+            .and_then(|table| table.get(key))
+            .and_then(toml::Value::as_array)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(toml::Value::as_str)
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the name of the library (or binary) crate of the package.
+    fn crate_name(&self) -> Result<String, anyhow::Error> {
+        let lib_name = self
+            .table(&["lib"])
+            .and_then(|lib| lib.get("name"))
+            .and_then(toml::Value::as_str);
+        let package_name = self
+            .table(&["package"])
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str);
+        lib_name
+            .or(package_name)
+            .map(|name| name.replace('-', "_"))
+            .ok_or_else(|| anyhow::anyhow!("missing package name in `{}`", self.dir.display()))
+    }
+
+    /// Returns the root file of the library crate of the package, or the
+    /// root file of its binary crate if it has no library.
+    fn entry(&self) -> Result<PathBuf, anyhow::Error> {
+        let lib_path = self
+            .table(&["lib"])
+            .and_then(|lib| lib.get("path"))
+            .and_then(toml::Value::as_str);
+        if let Some(path) = lib_path {
+            return Ok(self.dir.join(path));
+        }
+        ["src/lib.rs", "src/main.rs"]
+            .iter()
+            .map(|entry| self.dir.join(entry))
+            .find(|entry| entry.is_file())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "no `src/lib.rs` or `src/main.rs` found in `{}`",
+                    self.dir.display()
+                )
+            })
+    }
+
+    /// Returns the directories of the workspace members.
+    fn members(&self) -> Result<Vec<PathBuf>, anyhow::Error> {
+        let excluded = self
+            .strings(&["workspace"], "exclude")
+            .iter()
+            .map(|exclude| self.dir.join(exclude))
+            .collect::<Vec<_>>();
+        let mut members = Vec::new();
+        for member in self.strings(&["workspace"], "members") {
+            let pattern = self.dir.join(&member);
+            let pattern = pattern
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("invalid workspace member `{}`", member))?;
+            for path in glob::glob(pattern)? {
+                let path = path?;
+                if path.join("Cargo.toml").is_file() && !excluded.contains(&path) {
+                    members.push(path);
+                }
+            }
+        }
+        Ok(members)
+    }
+
+    /// Returns the path dependencies of the package by the name they are
+    /// available under in its source. Dependencies inherited from the
+    /// workspace are looked up in `workspace`.
+    fn path_dependencies(&self, workspace: &Manifest) -> Vec<(String, PathBuf)> {
+        let dependencies = match self.table(&["dependencies"]) {
+            Some(dependencies) => dependencies,
+            None => return Vec::new(),
+        };
+        let workspace_dependencies = workspace.table(&["workspace", "dependencies"]);
+        dependencies
+            .iter()
+            .filter_map(|(name, dependency)| {
+                let inherited = dependency
+                    .get("workspace")
+                    .and_then(toml::Value::as_bool)
+                    .unwrap_or(false);
+                let path = if inherited {
+                    let dependency = workspace_dependencies?.get(name)?;
+                    workspace.dir.join(dependency.get("path")?.as_str()?)
+                } else {
+                    self.dir.join(dependency.get("path")?.as_str()?)
+                };
+                Some((name.replace('-', "_"), path))
+            })
+            .collect()
+    }
+}
+
+/// Collects the crates of a workspace and their path dependencies, in the
+/// order they are found. `path` is either a `Cargo.toml` or the directory
+/// containing it. If the manifest is a package, it is collected as well.
+pub fn collect_crates(path: &Path) -> Result<Vec<SourceCrate>, anyhow::Error> {
+    let workspace = Manifest::read(path)?;
+    let mut queue = VecDeque::new();
+    if workspace.table(&["package"]).is_some() {
+        queue.push_back(workspace.dir.clone());
+    }
+    queue.extend(workspace.members()?);
+
+    let mut crates = Vec::new();
+    let mut visited = HashSet::new();
+    while let Some(dir) = queue.pop_front() {
+        if !visited.insert(fs::canonicalize(&dir)?) {
+            continue;
+        }
+        let manifest = Manifest::read(&dir)?;
+        let mut externs = HashMap::new();
+        for (extern_name, dependency_dir) in manifest.path_dependencies(&workspace) {
+            let dependency = Manifest::read(&dependency_dir)?;
+            externs.insert(extern_name, dependency.crate_name()?);
+            queue.push_back(dependency_dir);
+        }
+        crates.push(SourceCrate {
+            name: manifest.crate_name()?,
+            entry: manifest.entry()?,
+            externs,
+        });
+    }
+    Ok(crates)
+}